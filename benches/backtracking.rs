@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MPL-2.0
+
+extern crate criterion;
+use self::criterion::*;
+
+use pubgrub::range::Range;
+use pubgrub::solver::{resolve, OfflineDependencyProvider};
+use pubgrub::version::NumberVersion;
+
+const ROOT: u32 = 0;
+const A: u32 = 1;
+const X: u32 = 2;
+
+/// Registry where `a` is decided first, then every `b` narrows the versions of `x`,
+/// adding one derivation at each decision level.
+/// The last `b` conflicts with the newest `a`, so the solver backtracks
+/// over a history of `2 * count` assignments to pick the other `a`.
+fn registry(count: u32) -> OfflineDependencyProvider<u32, NumberVersion> {
+    let mut dependency_provider = OfflineDependencyProvider::new();
+    let b = |i: u32| X + 1 + i;
+    let root_deps = (0..count).map(|i| (b(i), Range::any()));
+    dependency_provider.add_dependencies(ROOT, 0, root_deps.chain([(A, Range::any())]));
+    dependency_provider.add_dependencies(A, 1, []);
+    dependency_provider.add_dependencies(A, 2, [(X, Range::higher_than(1))]);
+    for i in 0..count {
+        for v in 1..4 {
+            let x_deps = [(X, Range::strictly_lower_than(count - i))];
+            dependency_provider.add_dependencies(b(i), v, x_deps);
+        }
+    }
+    for v in 0..count {
+        dependency_provider.add_dependencies(X, v, []);
+    }
+    dependency_provider
+}
+
+fn bench_backtracking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backtracking");
+    group.sample_size(10);
+    for count in [1_000, 5_000] {
+        let dependency_provider = registry(count);
+        group.bench_function(BenchmarkId::new("history", 2 * count), |b| {
+            b.iter(|| resolve(&dependency_provider, ROOT, 0).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_backtracking);
+criterion_main!(benches);
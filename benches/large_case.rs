@@ -14,7 +14,7 @@ fn bench<'a, P: Package + Deserialize<'a>, V: Version + Hash + Deserialize<'a>>(
     b: &mut Bencher,
     case: &'a str,
 ) {
-    let dependency_provider: OfflineDependencyProvider<P, V> = ron::de::from_str(&case).unwrap();
+    let dependency_provider: OfflineDependencyProvider<P, V> = ron::de::from_str(case).unwrap();
 
     b.iter(|| {
         for p in dependency_provider.packages() {
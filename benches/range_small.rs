@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MPL-2.0
+
+extern crate criterion;
+use self::criterion::*;
+
+use pubgrub::range::Range;
+use pubgrub::version::NumberVersion;
+
+/// Range made of `count` disjoint intervals.
+/// Ranges of up to two intervals are stored inline, bigger ones on the heap.
+fn range_of(count: u32) -> Range<NumberVersion> {
+    (0..count)
+        .map(|i| (i * 10).into())
+        .fold(Range::none(), |acc, v: NumberVersion| {
+            acc.union(&Range::between(v, v.0 + 5))
+        })
+}
+
+fn bench_small(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_small");
+    for count in [1, 2, 3, 8] {
+        let r1 = range_of(count);
+        let r2 = Range::higher_than(3);
+        group.bench_function(BenchmarkId::new("clone", count), |b| {
+            b.iter(|| black_box(&r1).clone())
+        });
+        group.bench_function(BenchmarkId::new("intersection", count), |b| {
+            b.iter(|| black_box(&r1).intersection(black_box(&r2)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_small);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+
+extern crate criterion;
+use self::criterion::*;
+
+use pubgrub::range::Range;
+use pubgrub::version::NumberVersion;
+
+/// Range made of every `step` versions in `0..count`.
+fn sparse_range(count: u32, step: u32, offset: u32) -> Range<NumberVersion> {
+    (0..count)
+        .step_by(step as usize)
+        .fold(Range::none(), |acc, v| acc.union(&Range::exact(v + offset)))
+}
+
+fn bench_union(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_union");
+    for count in [10, 100, 1000] {
+        let r1 = sparse_range(count, 3, 0);
+        let r2 = sparse_range(count, 3, 1);
+        group.bench_function(BenchmarkId::new("direct", count), |b| {
+            b.iter(|| black_box(&r1).union(black_box(&r2)))
+        });
+        group.bench_function(BenchmarkId::new("de_morgan", count), |b| {
+            b.iter(|| {
+                black_box(&r1)
+                    .negate()
+                    .intersection(&black_box(&r2).negate())
+                    .negate()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_union);
+criterion_main!(benches);
@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Sets of versions within a small, known, list of versions.
+//!
+//! When all the existing versions of a package are known in advance,
+//! like in a registry index, a set of versions can be stored as one bit per existing version.
+//! Set operations are then performed one machine word at a time,
+//! which is much faster than merging the segments of [Range] values.
+//!
+//! A [BitRangeSet] only knows the positions of versions in that list,
+//! called the universe, which must be sorted and is passed to the conversion functions.
+//!
+//! ```
+//! # use pubgrub::bit_range_set::BitRangeSet;
+//! # use pubgrub::range::Range;
+//! # use pubgrub::version::NumberVersion;
+//! let universe: Vec<NumberVersion> = vec![1.into(), 2.into(), 4.into(), 7.into()];
+//! let r1 = BitRangeSet::from_range(&universe, &Range::between(2, 5));
+//! let r2 = BitRangeSet::from_range(&universe, &Range::higher_than(3));
+//! let both = r1.intersection(&r2);
+//! assert_eq!(both.indices().collect::<Vec<_>>(), vec![2]);
+//! assert_eq!(both.to_range(&universe), Range::between_exclusive(2, 7));
+//! ```
+
+use std::fmt;
+use std::ops::Bound::{self, Excluded, Unbounded};
+
+use crate::range::Range;
+use crate::version::Version;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of versions among a known list of versions, stored as a bitmask of their positions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitRangeSet {
+    /// Bits past the length of the universe are always zero.
+    words: Vec<u64>,
+    len: usize,
+}
+
+// Set building blocks.
+impl BitRangeSet {
+    /// Empty set of versions, in a universe of `len` versions.
+    pub fn none(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    /// Set of all the versions of a universe of `len` versions.
+    pub fn any(len: usize) -> Self {
+        Self::none(len).negate()
+    }
+
+    /// Set containing exactly the versions at the given positions
+    /// in a universe of `len` versions.
+    ///
+    /// Panics if a position is not lower than `len`.
+    pub fn from_indices(len: usize, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = Self::none(len);
+        for index in indices {
+            assert!(index < len, "index {} out of a universe of {}", index, len);
+            set.words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+        }
+        set
+    }
+
+    /// Set of the versions of the universe contained in a range.
+    /// The universe must be sorted.
+    pub fn from_range<V: Version>(universe: &[V], range: &Range<V>) -> Self {
+        let contained = range.contains_many(universe.iter());
+        Self::from_indices(
+            universe.len(),
+            contained
+                .enumerate()
+                .filter(|&(_, contained)| contained)
+                .map(|(index, _)| index),
+        )
+    }
+
+    /// Range containing the versions of this set, and none of the other versions of the universe.
+    /// Bounds are placed like in [Range::simplify], to keep as few segments as possible.
+    /// The universe must be sorted and have the length this set was built with.
+    pub fn to_range<V: Version>(&self, universe: &[V]) -> Range<V> {
+        assert_eq!(universe.len(), self.len, "universe of a different length");
+        let mut intervals = Vec::new();
+        let mut current: Option<Bound<V>> = None;
+        for (index, version) in universe.iter().enumerate() {
+            match (self.contains(index), current.is_some()) {
+                (true, false) if index == 0 => current = Some(Unbounded),
+                (true, false) => current = Some(Excluded(universe[index - 1].clone())),
+                (false, true) => {
+                    intervals.push((current.take().unwrap(), Excluded(version.clone())))
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = current {
+            intervals.push((start, Unbounded));
+        }
+        intervals.into_iter().collect()
+    }
+}
+
+// Set operations.
+impl BitRangeSet {
+    /// Compute the complement set of versions within the universe.
+    pub fn negate(&self) -> Self {
+        let mut words: Vec<u64> = self.words.iter().map(|word| !word).collect();
+        if let Some(last) = words.last_mut() {
+            let used_bits = self.len % WORD_BITS;
+            if used_bits != 0 {
+                *last &= (1 << used_bits) - 1;
+            }
+        }
+        Self {
+            words,
+            len: self.len,
+        }
+    }
+
+    /// Compute the union of two sets of versions.
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_words(other, |w1, w2| w1 | w2)
+    }
+
+    /// Compute the intersection of two sets of versions.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_words(other, |w1, w2| w1 & w2)
+    }
+
+    /// Compute the set of versions in this set but not in the other one.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_words(other, |w1, w2| w1 & !w2)
+    }
+
+    /// Check whether all the versions of this set are also in the other one.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.check_universe(other);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(w1, w2)| w1 & !w2 == 0)
+    }
+
+    /// Check whether the two sets have at least one version in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.check_universe(other);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .any(|(w1, w2)| w1 & w2 != 0)
+    }
+
+    /// Helper function combining the words of two sets of the same universe.
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        self.check_universe(other);
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&w1, &w2)| f(w1, w2))
+                .collect(),
+            len: self.len,
+        }
+    }
+
+    fn check_universe(&self, other: &Self) {
+        assert_eq!(
+            self.len, other.len,
+            "sets of universes of different lengths"
+        );
+    }
+}
+
+// Other useful functions.
+impl BitRangeSet {
+    /// Number of versions in the universe of this set.
+    pub fn universe_len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the version at a given position in the universe is in this set.
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    /// Check whether this set contains no version at all.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Check whether this set contains all the versions of the universe.
+    pub fn is_full(&self) -> bool {
+        self.negate().is_empty()
+    }
+
+    /// Number of versions in this set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over the positions in the universe of the versions in this set, in increasing order.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(move |&index| self.contains(index))
+    }
+}
+
+/// Sets are written as the positions of their versions, since they do not hold the versions.
+impl fmt::Display for BitRangeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indices: Vec<_> = self.indices().map(|index| index.to_string()).collect();
+        write!(f, "{{ {} }} of {}", indices.join(", "), self.len)
+    }
+}
+
+// TESTS #######################################################################
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::version::NumberVersion;
+
+    use super::*;
+
+    fn universe_strat() -> impl Strategy<Value = Vec<NumberVersion>> {
+        prop::collection::btree_set(0..100u32, 0..150)
+            .prop_map(|versions| versions.into_iter().map(NumberVersion).collect())
+    }
+
+    fn range_strat() -> impl Strategy<Value = Range<NumberVersion>> {
+        prop::collection::vec((0..100u32, any::<bool>()), 0..8).prop_map(|bounds| {
+            Range::from_bounds_list(
+                bounds
+                    .into_iter()
+                    .map(|(v, included)| (NumberVersion(v), included))
+                    .collect(),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn operations_match_range(universe in universe_strat(), r1 in range_strat(), r2 in range_strat()) {
+            let s1 = BitRangeSet::from_range(&universe, &r1);
+            let s2 = BitRangeSet::from_range(&universe, &r2);
+            let same = |set: BitRangeSet, range: Range<NumberVersion>| {
+                assert_eq!(set, BitRangeSet::from_range(&universe, &range));
+            };
+            same(s1.negate(), r1.negate());
+            same(s1.union(&s2), r1.union(&r2));
+            same(s1.intersection(&s2), r1.intersection(&r2));
+            same(s1.difference(&s2), r1.difference(&r2));
+            assert_eq!(s1.count(), universe.iter().filter(|v| r1.contains(v)).count());
+        }
+
+        #[test]
+        fn predicates_match_operations(universe in universe_strat(), r1 in range_strat(), r2 in range_strat()) {
+            let s1 = BitRangeSet::from_range(&universe, &r1);
+            let s2 = BitRangeSet::from_range(&universe, &r2);
+            assert_eq!(s1.is_subset_of(&s2), s1.intersection(&s2) == s1);
+            assert_eq!(s1.overlaps(&s2), !s1.intersection(&s2).is_empty());
+            assert!(s1.union(&s1.negate()).is_full());
+        }
+
+        #[test]
+        fn to_range_is_simplify(universe in universe_strat(), range in range_strat()) {
+            let set = BitRangeSet::from_range(&universe, &range);
+            assert_eq!(set.to_range(&universe), range.simplify(&universe));
+        }
+    }
+
+    #[test]
+    fn display() {
+        let set = BitRangeSet::from_indices(70, vec![65, 2]);
+        assert_eq!(set.to_string(), "{ 2, 65 } of 70");
+        assert_eq!(set.count(), 2);
+        assert_eq!(set.negate().count(), 68);
+    }
+}
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Sets of unordered version identifiers.
+//!
+//! Some domains identify versions with opaque values,
+//! like git commit hashes or content hashes, that have no meaningful order.
+//! They cannot be described with [Range](crate::range::Range) intervals,
+//! but the same set algebra is available with a [DiscreteSet],
+//! which is either a finite set of identifiers or the complement of one.
+//!
+//! ```
+//! # use pubgrub::discrete_set::DiscreteSet;
+//! let tested = DiscreteSet::from_versions(["a1f3", "77be", "c0de"]);
+//! let yanked = DiscreteSet::exact("77be");
+//! let allowed = tested.intersection(&yanked.negate());
+//! assert!(allowed.contains(&"a1f3"));
+//! assert!(!allowed.contains(&"77be"));
+//! assert_eq!(allowed, DiscreteSet::from_versions(["a1f3", "c0de"]));
+//! ```
+
+use std::fmt;
+use std::hash::Hash;
+
+use rustc_hash::FxHashSet;
+
+/// A set of versions that are only compared for equality.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscreteSet<V: Hash + Eq> {
+    /// When true, the set contains all the versions except the listed ones.
+    negated: bool,
+    versions: FxHashSet<V>,
+}
+
+// Set building blocks.
+impl<V: Hash + Eq> DiscreteSet<V> {
+    /// Empty set of versions.
+    pub fn none() -> Self {
+        Self {
+            negated: false,
+            versions: FxHashSet::default(),
+        }
+    }
+
+    /// Set of all possible versions.
+    pub fn any() -> Self {
+        Self {
+            negated: true,
+            versions: FxHashSet::default(),
+        }
+    }
+
+    /// Set containing exactly one version.
+    pub fn exact(v: impl Into<V>) -> Self {
+        Self::from_versions(std::iter::once(v.into()))
+    }
+
+    /// Set containing exactly the given versions.
+    pub fn from_versions(versions: impl IntoIterator<Item = V>) -> Self {
+        Self {
+            negated: false,
+            versions: versions.into_iter().collect(),
+        }
+    }
+}
+
+// Set operations.
+impl<V: Hash + Eq + Clone> DiscreteSet<V> {
+    /// Compute the complement set of versions.
+    pub fn negate(&self) -> Self {
+        Self {
+            negated: !self.negated,
+            versions: self.versions.clone(),
+        }
+    }
+
+    /// Compute the union of two sets of versions.
+    pub fn union(&self, other: &Self) -> Self {
+        match (self.negated, other.negated) {
+            (false, false) => Self::listed(false, self.versions.union(&other.versions)),
+            (true, true) => Self::listed(true, self.versions.intersection(&other.versions)),
+            (true, false) => Self::listed(true, self.versions.difference(&other.versions)),
+            (false, true) => Self::listed(true, other.versions.difference(&self.versions)),
+        }
+    }
+
+    /// Compute the intersection of two sets of versions.
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (self.negated, other.negated) {
+            (false, false) => Self::listed(false, self.versions.intersection(&other.versions)),
+            (true, true) => Self::listed(true, self.versions.union(&other.versions)),
+            (true, false) => Self::listed(false, other.versions.difference(&self.versions)),
+            (false, true) => Self::listed(false, self.versions.difference(&other.versions)),
+        }
+    }
+
+    /// Compute the set of versions in this set but not in the other one.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.negate())
+    }
+
+    /// Helper function building a set from the listed versions.
+    fn listed<'a>(negated: bool, versions: impl Iterator<Item = &'a V>) -> Self
+    where
+        V: 'a,
+    {
+        Self {
+            negated,
+            versions: versions.cloned().collect(),
+        }
+    }
+}
+
+// Other useful functions.
+impl<V: Hash + Eq> DiscreteSet<V> {
+    /// Check if a set of versions contains a given version.
+    pub fn contains(&self, version: &V) -> bool {
+        self.versions.contains(version) != self.negated
+    }
+
+    /// Check whether this set contains no version at all.
+    pub fn is_empty(&self) -> bool {
+        !self.negated && self.versions.is_empty()
+    }
+
+    /// Check whether this set contains all possible versions.
+    pub fn is_full(&self) -> bool {
+        self.negated && self.versions.is_empty()
+    }
+
+    /// Check whether all the versions of this set are also in the other one.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        match (self.negated, other.negated) {
+            (false, false) => self.versions.is_subset(&other.versions),
+            (true, true) => other.versions.is_subset(&self.versions),
+            // A complement is infinite so it never fits in a finite set.
+            (true, false) => false,
+            (false, true) => self.versions.is_disjoint(&other.versions),
+        }
+    }
+
+    /// Check whether the two sets have at least one version in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self.negated, other.negated) {
+            (false, false) => !self.versions.is_disjoint(&other.versions),
+            (true, true) => true,
+            (true, false) => !other.versions.is_subset(&self.versions),
+            (false, true) => !self.versions.is_subset(&other.versions),
+        }
+    }
+}
+
+/// Versions are written in lexicographic order of their string representations,
+/// since they have no order of their own.
+impl<V: Hash + Eq + fmt::Display> fmt::Display for DiscreteSet<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions: Vec<_> = self.versions.iter().map(|v| v.to_string()).collect();
+        versions.sort_unstable();
+        match (self.negated, versions.is_empty()) {
+            (false, true) => write!(f, "∅"),
+            (true, true) => write!(f, "∗"),
+            (false, false) => write!(f, "{{ {} }}", versions.join(", ")),
+            (true, false) => write!(f, "not {{ {} }}", versions.join(", ")),
+        }
+    }
+}
+
+// TESTS #######################################################################
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn strategy() -> impl Strategy<Value = DiscreteSet<u8>> {
+        (any::<bool>(), prop::collection::hash_set(0..8u8, 0..5)).prop_map(|(negated, versions)| {
+            DiscreteSet {
+                negated,
+                versions: versions.into_iter().collect(),
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn operations_match_membership(s1 in strategy(), s2 in strategy(), version in 0..10u8) {
+            let (in1, in2) = (s1.contains(&version), s2.contains(&version));
+            assert_eq!(s1.negate().contains(&version), !in1);
+            assert_eq!(s1.union(&s2).contains(&version), in1 || in2);
+            assert_eq!(s1.intersection(&s2).contains(&version), in1 && in2);
+            assert_eq!(s1.difference(&s2).contains(&version), in1 && !in2);
+        }
+
+        #[test]
+        fn predicates_match_operations(s1 in strategy(), s2 in strategy()) {
+            assert_eq!(s1.is_subset_of(&s2), s1.intersection(&s2) == s1);
+            assert_eq!(s1.overlaps(&s2), !s1.intersection(&s2).is_empty());
+            assert!(s1.union(&s1.negate()).is_full());
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(DiscreteSet::<u8>::none().to_string(), "∅");
+        assert_eq!(DiscreteSet::<u8>::any().to_string(), "∗");
+        let set = DiscreteSet::from_versions([3u8, 1]);
+        assert_eq!(set.to_string(), "{ 1, 3 }");
+        assert_eq!(set.negate().to_string(), "not { 1, 3 }");
+    }
+}
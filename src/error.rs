@@ -70,7 +70,45 @@ pub enum PubGrubError<P: Package, V: Version> {
     #[error("We should cancel")]
     ErrorInShouldCancel(Box<dyn std::error::Error>),
 
+    /// Error arising when the solver made more decisions than allowed
+    /// by the limit given to [resolve_with_limit](crate::solver::resolve_with_limit).
+    #[error("Resolution stopped after {decisions_made} decisions")]
+    LimitExceeded {
+        /// Number of decisions made before stopping.
+        decisions_made: u64,
+    },
+
+    /// Error arising when the solver ran for longer than the timeout
+    /// of its [SolverConfig](crate::solver::SolverConfig).
+    #[error("Resolution timed out after {elapsed:?}")]
+    Timeout {
+        /// Time spent solving before stopping.
+        elapsed: std::time::Duration,
+    },
+
+    /// Error arising when the selected packages depend on each other in a cycle,
+    /// and cycles are forbidden by the [SolverConfig](crate::solver::SolverConfig).
+    #[error("Dependency cycle: {}", display_cycle(.path))]
+    Cycle {
+        /// Packages and versions of the cycle, each one depending on the next one,
+        /// and the last one depending on the first one.
+        path: Vec<(P, V)>,
+    },
+
+    /// Error arising when the cancellation flag
+    /// of the [SolverConfig](crate::solver::SolverConfig) was set.
+    #[error("Resolution was cancelled")]
+    Cancelled,
+
     /// Something unexpected happened.
     #[error("{0}")]
     Failure(String),
 }
+
+fn display_cycle<P: Package, V: Version>(path: &[(P, V)]) -> String {
+    path.iter()
+        .chain(path.first())
+        .map(|(p, v)| format!("{} {}", p, v))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
@@ -2,7 +2,7 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
     marker::PhantomData,
-    ops::{Index, Range},
+    ops::Index,
 };
 
 /// The index of a value allocated in an arena that holds `T`s.
@@ -55,15 +55,10 @@ impl<T> Id<T> {
     }
     fn from(n: u32) -> Self {
         Self {
-            raw: n as u32,
+            raw: n,
             _ty: PhantomData,
         }
     }
-    pub fn range_to_iter(range: Range<Self>) -> impl Iterator<Item = Self> {
-        let start = range.start.raw;
-        let end = range.end.raw;
-        (start..end).map(Self::from)
-    }
 }
 
 /// Yet another index-based arena.
@@ -97,13 +92,8 @@ impl<T> Arena<T> {
         Id::from(raw as u32)
     }
 
-    pub fn alloc_iter<I: Iterator<Item = T>>(&mut self, values: I) -> Range<Id<T>> {
-        let start = Id::from(self.data.len() as u32);
-        values.for_each(|v| {
-            self.alloc(v);
-        });
-        let end = Id::from(self.data.len() as u32);
-        Range { start, end }
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
     }
 }
 
@@ -113,10 +103,3 @@ impl<T> Index<Id<T>> for Arena<T> {
         &self.data[id.raw as usize]
     }
 }
-
-impl<T> Index<Range<Id<T>>> for Arena<T> {
-    type Output = [T];
-    fn index(&self, id: Range<Id<T>>) -> &[T] {
-        &self.data[(id.start.raw as usize)..(id.end.raw as usize)]
-    }
-}
@@ -14,8 +14,10 @@ use crate::internal::partial_solution::SatisfierSearch::{
 use crate::internal::partial_solution::{DecisionLevel, PartialSolution};
 use crate::internal::small_vec::SmallVec;
 use crate::package::Package;
+use crate::range::Range;
 use crate::report::DerivationTree;
-use crate::solver::DependencyConstraints;
+use crate::solver::{DependencyConstraints, SolveStats};
+use crate::term::Term;
 use crate::type_aliases::Map;
 use crate::version::Version;
 
@@ -27,6 +29,14 @@ pub struct State<P: Package, V: Version> {
 
     incompatibilities: Map<P, Vec<IncompId<P, V>>>,
 
+    /// The incompatibilities coming from the dependencies of a package on another,
+    /// which are candidates to be merged with new ones for the same dependency.
+    merged_dependencies: Map<(P, P), SmallVec<IncompId<P, V>>>,
+
+    /// The incompatibilities given to [add_incompatibility](Self::add_incompatibility),
+    /// by the hash of their packages, to avoid adding the same one twice.
+    added_incompatibilities: Map<u64, SmallVec<IncompId<P, V>>>,
+
     /// Store the ids of incompatibilities that are already contradicted
     /// and will stay that way until the next conflict and backtrack is operated.
     contradicted_incompatibilities: rustc_hash::FxHashSet<IncompId<P, V>>,
@@ -42,6 +52,9 @@ pub struct State<P: Package, V: Version> {
     /// It can definitely be a local variable to that method, but
     /// this way we can reuse the same allocation for better performance.
     unit_propagation_buffer: SmallVec<P>,
+
+    /// Counters of the work done so far.
+    stats: SolveStats,
 }
 
 impl<P: Package, V: Version> State<P, V> {
@@ -58,37 +71,130 @@ impl<P: Package, V: Version> State<P, V> {
             root_package,
             root_version,
             incompatibilities,
+            merged_dependencies: Map::default(),
+            added_incompatibilities: Map::default(),
             contradicted_incompatibilities: rustc_hash::FxHashSet::default(),
             partial_solution: PartialSolution::empty(),
             incompatibility_store,
             unit_propagation_buffer: SmallVec::Empty,
+            stats: SolveStats::default(),
         }
     }
 
-    /// Add an incompatibility to the state.
-    pub fn add_incompatibility(&mut self, incompat: Incompatibility<P, V>) {
+    /// Add an incompatibility to the state,
+    /// unless the same one was already added.
+    /// Returns the id of the incompatibility in the store.
+    pub fn add_incompatibility(&mut self, incompat: Incompatibility<P, V>) -> IncompId<P, V> {
+        // Derived incompatibilities are never the same since their causes differ.
+        let hash = incompat
+            .causes()
+            .is_none()
+            .then(|| incompat.packages_hash());
+        if let Some(hash) = hash {
+            if let Some(ids) = self.added_incompatibilities.get(&hash) {
+                if let Some(&id) = ids
+                    .iter()
+                    .find(|&&id| self.incompatibility_store[id].is_same_as(&incompat))
+                {
+                    return id;
+                }
+            }
+        }
         let id = self.incompatibility_store.alloc(incompat);
+        if let Some(hash) = hash {
+            self.added_incompatibilities
+                .entry(hash)
+                .or_default()
+                .push(id);
+        }
+        self.stats.incompatibilities_added += 1;
         self.merge_incompatibility(id);
+        id
     }
 
-    /// Add an incompatibility to the state.
+    /// Forbid the versions of a package outside of the given range,
+    /// regardless of what the other packages require.
+    /// Meant to be called before solving begins,
+    /// for example to enforce a policy or a platform constraint.
+    ///
+    /// The constraint is an incompatibility of the `NoVersions` kind,
+    /// so in the derivation tree of an error report it appears as
+    /// "there is no available version for package in (not range)".
+    pub fn add_global_constraint(&mut self, package: P, range: Range<V>) {
+        if !range.is_full() {
+            self.add_incompatibility(Incompatibility::no_versions(
+                package,
+                Term::Positive(range.negate()),
+            ));
+        }
+    }
+
+    /// Add the incompatibilities of the dependencies of a package version to the state,
+    /// through [add_incompatibility](Self::add_incompatibility)
+    /// so that a dependency already added is not added twice.
+    /// Returns their ids in the store.
     pub fn add_incompatibility_from_dependencies(
         &mut self,
         package: P,
         version: V,
         deps: &DependencyConstraints<P, V>,
-    ) -> std::ops::Range<IncompId<P, V>> {
-        // Create incompatibilities and allocate them in the store.
-        let new_incompats_id_range = self
-            .incompatibility_store
-            .alloc_iter(deps.iter().map(|dep| {
-                Incompatibility::from_dependency(package.clone(), version.clone(), dep)
-            }));
-        // Merge the newly created incompatibilities with the older ones.
-        for id in IncompId::range_to_iter(new_incompats_id_range.clone()) {
-            self.merge_incompatibility(id);
+    ) -> Vec<IncompId<P, V>> {
+        let is_root_dep = package == self.root_package;
+        deps.iter()
+            .map(|dep| {
+                self.add_incompatibility(Incompatibility::from_dependency(
+                    package.clone(),
+                    version.clone(),
+                    dep,
+                    is_root_dep,
+                ))
+            })
+            .collect()
+    }
+
+    /// Add a decision to the partial solution.
+    pub fn add_decision(&mut self, package: P, version: V) {
+        self.partial_solution.add_decision(package, version);
+        self.record_decision();
+    }
+
+    /// Add a version to the partial solution as a decision,
+    /// if its dependencies do not produce a conflict.
+    pub fn add_version(
+        &mut self,
+        package: P,
+        version: V,
+        new_incompatibilities: &[IncompId<P, V>],
+    ) {
+        let level = self.partial_solution.current_decision_level();
+        self.partial_solution.add_version(
+            package,
+            version,
+            new_incompatibilities,
+            &self.incompatibility_store,
+        );
+        if self.partial_solution.current_decision_level() != level {
+            self.record_decision();
         }
-        new_incompats_id_range
+    }
+
+    fn record_decision(&mut self) {
+        self.stats.decisions += 1;
+        let DecisionLevel(level) = self.partial_solution.current_decision_level();
+        self.stats.max_decision_level = self.stats.max_decision_level.max(level);
+    }
+
+    /// Counters of the work done so far by the solver.
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    /// Estimate the memory used by all incompatibilities in the store, in bytes.
+    pub fn total_incompatibility_memory(&self) -> usize {
+        self.incompatibility_store
+            .iter()
+            .map(|incompat| incompat.size_in_bytes())
+            .sum()
     }
 
     /// Check if an incompatibility is terminal.
@@ -105,12 +211,24 @@ impl<P: Package, V: Version> State<P, V> {
             // Iterate over incompatibilities in reverse order
             // to evaluate first the newest incompatibilities.
             let mut conflict_id = None;
+            let decided_version = self
+                .partial_solution
+                .decided_version(&current_package)
+                .cloned();
             // We only care about incompatibilities if it contains the current package.
             for &incompat_id in self.incompatibilities[&current_package].iter().rev() {
                 if self.contradicted_incompatibilities.contains(&incompat_id) {
                     continue;
                 }
                 let current_incompat = &self.incompatibility_store[incompat_id];
+                // If a version was picked for the current package and the incompatibility
+                // does not cover it, the incompatibility is contradicted by that decision.
+                if let Some(version) = &decided_version {
+                    if !current_incompat.covers(&current_package, version) {
+                        self.contradicted_incompatibilities.insert(incompat_id);
+                        continue;
+                    }
+                }
                 match self.partial_solution.relation(current_incompat) {
                     // If the partial solution satisfies the incompatibility
                     // we must perform conflict resolution.
@@ -130,6 +248,7 @@ impl<P: Package, V: Version> State<P, V> {
                             incompat_id,
                             &self.incompatibility_store,
                         );
+                        self.stats.derivations += 1;
                         // With the partial solution updated, the incompatibility is now contradicted.
                         self.contradicted_incompatibilities.insert(incompat_id);
                     }
@@ -149,6 +268,7 @@ impl<P: Package, V: Version> State<P, V> {
                     root_cause,
                     &self.incompatibility_store,
                 );
+                self.stats.derivations += 1;
                 // After conflict resolution and the partial solution update,
                 // the root cause incompatibility is now contradicted.
                 self.contradicted_incompatibilities.insert(root_cause);
@@ -199,6 +319,7 @@ impl<P: Package, V: Version> State<P, V> {
                         );
                         log::info!("prior cause: {}", prior_cause);
                         current_incompat_id = self.incompatibility_store.alloc(prior_cause);
+                        self.stats.incompatibilities_added += 1;
                         current_incompat_changed = true;
                     }
                 }
@@ -215,9 +336,46 @@ impl<P: Package, V: Version> State<P, V> {
     ) {
         self.partial_solution
             .backtrack(decision_level, &self.incompatibility_store);
+        self.stats.backtracks += 1;
         self.contradicted_incompatibilities.clear();
         if incompat_changed {
             self.merge_incompatibility(incompat);
+            self.remove_redundant_incompatibilities(incompat);
+        }
+    }
+
+    /// Remove incompatibilities that are redundant given a new one,
+    /// or the new one if it is redundant given another one,
+    /// from the lists of incompatibilities of all packages.
+    /// When two incompatibilities are equivalent, the oldest one is kept.
+    /// They stay in the store since they may be the cause of others.
+    ///
+    /// Only the lists of the packages of the new incompatibility are scanned:
+    /// an incompatibility it is redundant given has a subset of its packages,
+    /// and one that is redundant given it has all of them.
+    fn remove_redundant_incompatibilities(&mut self, new: IncompId<P, V>) {
+        let store = &self.incompatibility_store;
+        let ids_of = |package: &P| self.incompatibilities.get(package).into_iter().flatten();
+        let new_is_redundant = store[new].iter().any(|(package, _)| {
+            ids_of(package).any(|&id| id != new && store[new].is_redundant_given(&store[id]))
+        });
+        let redundant: Vec<_> = if new_is_redundant {
+            vec![new]
+        } else {
+            match store[new].iter().next() {
+                Some((package, _)) => ids_of(package)
+                    .copied()
+                    .filter(|&id| id != new && store[id].is_redundant_given(&store[new]))
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+        for id in redundant {
+            for (package, _) in store[id].iter() {
+                if let Some(ids) = self.incompatibilities.get_mut(package) {
+                    ids.retain(|&other| other != id);
+                }
+            }
         }
     }
 
@@ -237,10 +395,35 @@ impl<P: Package, V: Version> State<P, V> {
     /// We could collapse them into { foo (1.0.0 ∪ 1.1.0), not bar ^1.0.0 }
     /// without having to check the existence of other versions though.
     ///
-    /// Here we do the simple stupid thing of just growing the Vec.
-    /// It may not be trivial since those incompatibilities
-    /// may already have derived others.
-    fn merge_incompatibility(&mut self, id: IncompId<P, V>) {
+    /// Here we collapse dependencies on the same range
+    /// into { foo (1.0.0 ∪ 1.1.0), not bar ^1.0.0 }.
+    /// Since those incompatibilities may already have derived others,
+    /// they are not modified in the store.
+    /// The merged incompatibility is a new one replacing them in the lists of each package.
+    fn merge_incompatibility(&mut self, mut id: IncompId<P, V>) {
+        if let Some((p1, p2)) = self.incompatibility_store[id].as_dependency() {
+            let store = &self.incompatibility_store;
+            let past_deps = self
+                .merged_dependencies
+                .entry((p1.clone(), p2.clone()))
+                .or_default();
+            let merge = past_deps.iter().enumerate().find_map(|(i, &past)| {
+                Incompatibility::try_union(&store[past], &store[id]).map(|merged| (i, past, merged))
+            });
+            match merge {
+                Some((i, past, merged)) => {
+                    let new = self.incompatibility_store.alloc(merged);
+                    for (pkg, _term) in self.incompatibility_store[new].iter() {
+                        if let Some(ids) = self.incompatibilities.get_mut(pkg) {
+                            ids.retain(|&old| old != past);
+                        }
+                    }
+                    past_deps.as_mut_slice()[i] = new;
+                    id = new;
+                }
+                None => past_deps.push(id),
+            }
+        }
         for (pkg, _term) in self.incompatibility_store[id].iter() {
             self.incompatibilities
                 .entry(pkg.clone())
@@ -274,3 +457,127 @@ impl<P: Package, V: Version> State<P, V> {
         shared_ids
     }
 }
+
+// TESTS #######################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::NumberVersion;
+
+    #[test]
+    fn merge_dependencies_on_the_same_range() {
+        let mut state = State::<&str, NumberVersion>::init("root", NumberVersion(0));
+        let mut deps = DependencyConstraints::default();
+        deps.insert("bar", Range::between(1, 2));
+        state.add_incompatibility_from_dependencies("foo", NumberVersion(1), &deps);
+        state.add_incompatibility_from_dependencies("foo", NumberVersion(2), &deps);
+        let mut other_deps = DependencyConstraints::default();
+        other_deps.insert("bar", Range::between(2, 3));
+        state.add_incompatibility_from_dependencies("foo", NumberVersion(3), &other_deps);
+
+        // The two first dependencies are merged, the third one is on another range.
+        assert_eq!(state.incompatibilities[&"foo"].len(), 2);
+        assert_eq!(state.incompatibilities[&"bar"].len(), 2);
+        let merged = &state.incompatibility_store[state.incompatibilities[&"foo"][0]];
+        assert_eq!(
+            merged.get(&"foo"),
+            Some(&Term::Positive(Range::between(1, 3)))
+        );
+        assert_eq!(
+            merged.get(&"bar"),
+            Some(&Term::Negative(Range::between(1, 2)))
+        );
+        // The original incompatibilities stay in the store.
+        assert_eq!(state.incompatibility_store.iter().count(), 5);
+    }
+
+    #[test]
+    fn global_constraint() {
+        let mut state = State::<&str, NumberVersion>::init("root", NumberVersion(0));
+        state.add_global_constraint("a", Range::between(1, 3));
+        // Allowing every version is no constraint at all.
+        state.add_global_constraint("b", Range::any());
+        assert!(!state.incompatibilities.contains_key(&"b"));
+        let mut root_deps = DependencyConstraints::default();
+        root_deps.insert("a", Range::higher_than(2));
+        state.add_incompatibility_from_dependencies("root", NumberVersion(0), &root_deps);
+
+        state.unit_propagation("root").unwrap();
+        state.add_decision("root", NumberVersion(0));
+        state.unit_propagation("root").unwrap();
+        assert_eq!(
+            state.partial_solution.term_intersection_for_package(&"a"),
+            Some(&Term::Positive(Range::between(2, 3)))
+        );
+    }
+
+    #[test]
+    fn add_the_same_incompatibility_once() {
+        let mut state = State::<&str, NumberVersion>::init("root", NumberVersion(0));
+        let dep = Incompatibility::from_dependency(
+            "a",
+            NumberVersion(1),
+            (&"b", &Range::between(2, 4)),
+            false,
+        );
+        let id = state.add_incompatibility(dep.clone());
+        assert_eq!(state.add_incompatibility(dep), id);
+        assert_eq!(state.incompatibility_store.iter().count(), 2);
+        assert_eq!(state.incompatibilities[&"a"], vec![id]);
+
+        // Same terms but another kind.
+        let unavailable = Incompatibility::unavailable_dependencies("a", NumberVersion(1));
+        let no_versions = Incompatibility::no_versions("a", Term::exact(NumberVersion(1)));
+        assert_ne!(
+            state.add_incompatibility(unavailable),
+            state.add_incompatibility(no_versions)
+        );
+        assert_eq!(state.incompatibility_store.iter().count(), 4);
+    }
+
+    #[test]
+    fn add_the_same_dependency_once() {
+        let mut state = State::<&str, NumberVersion>::init("root", NumberVersion(0));
+        let mut deps = DependencyConstraints::default();
+        deps.insert("b", Range::between(2, 4));
+        let ids = state.add_incompatibility_from_dependencies("a", NumberVersion(1), &deps);
+        assert_eq!(
+            state.add_incompatibility_from_dependencies("a", NumberVersion(1), &deps),
+            ids
+        );
+        assert_eq!(state.incompatibility_store.iter().count(), 2);
+        assert_eq!(state.stats().incompatibilities_added, 1);
+    }
+
+    #[test]
+    fn incompatibility_memory() {
+        let mut state = State::<&str, NumberVersion>::init("root", NumberVersion(0));
+        let initial_memory = state.total_incompatibility_memory();
+        let inline_range = Range::between(1, 2);
+        let scattered_range = Range::exact(1)
+            .union(&Range::exact(3))
+            .union(&Range::exact(5));
+        let inline = state.add_incompatibility(Incompatibility::no_versions(
+            "a",
+            Term::Positive(inline_range),
+        ));
+        let scattered = state.add_incompatibility(Incompatibility::no_versions(
+            "b",
+            Term::Positive(scattered_range),
+        ));
+
+        // Ranges with few segments are stored without heap allocations.
+        let inline_size = state.incompatibility_store[inline].size_in_bytes();
+        let scattered_size = state.incompatibility_store[scattered].size_in_bytes();
+        assert_eq!(
+            inline_size,
+            std::mem::size_of::<Incompatibility<&str, NumberVersion>>()
+        );
+        assert!(scattered_size > inline_size);
+        assert_eq!(
+            state.total_incompatibility_memory(),
+            initial_memory + inline_size + scattered_size
+        );
+    }
+}
@@ -38,7 +38,7 @@ pub struct Incompatibility<P: Package, V: Version> {
 /// Type alias of unique identifiers for incompatibilities.
 pub type IncompId<P, V> = Id<Incompatibility<P, V>>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Kind<P: Package, V: Version> {
     /// Initial incompatibility aiming at picking the root package for the first decision.
     NotRoot(P, V),
@@ -48,6 +48,8 @@ enum Kind<P: Package, V: Version> {
     UnavailableDependencies(P, Range<V>),
     /// Incompatibility coming from the dependencies of a given package.
     FromDependencyOf(P, Range<V>, P, Range<V>),
+    /// Incompatibility coming from the dependencies of the root package.
+    RootDependency(P, Range<V>),
     /// Derived from two causes. Stores cause ids.
     DerivedFrom(IncompId<P, V>, IncompId<P, V>),
 }
@@ -106,15 +108,27 @@ impl<P: Package, V: Version> Incompatibility<P, V> {
     }
 
     /// Build an incompatibility from a given dependency.
-    pub fn from_dependency(package: P, version: V, dep: (&P, &Range<V>)) -> Self {
+    /// Dependencies of the root package are remembered as such,
+    /// to be reported as root requirements.
+    pub fn from_dependency(
+        package: P,
+        version: V,
+        dep: (&P, &Range<V>),
+        is_root_dep: bool,
+    ) -> Self {
         let range1 = Range::exact(version);
         let (p2, range2) = dep;
+        let kind = if is_root_dep {
+            Kind::RootDependency(p2.clone(), range2.clone())
+        } else {
+            Kind::FromDependencyOf(package.clone(), range1.clone(), p2.clone(), range2.clone())
+        };
         Self {
             package_terms: SmallMap::Two([
-                (package.clone(), Term::Positive(range1.clone())),
+                (package, Term::Positive(range1)),
                 (p2.clone(), Term::Negative(range2.clone())),
             ]),
-            kind: Kind::FromDependencyOf(package, range1, p2.clone(), range2.clone()),
+            kind,
         }
     }
 
@@ -143,6 +157,69 @@ impl<P: Package, V: Version> Incompatibility<P, V> {
         }
     }
 
+    /// Merge two incompatibilities with the same packages
+    /// and differing in exactly one term into a single one,
+    /// where that term is replaced by the union of both.
+    /// Returns [None] if the incompatibilities cannot be merged,
+    /// including when they do not come from the same kind of external fact.
+    pub fn try_union(a: &Self, b: &Self) -> Option<Self> {
+        if a.package_terms.len() != b.package_terms.len() {
+            return None;
+        }
+        let mut differing = None;
+        for (package, term_a) in a.package_terms.iter() {
+            let term_b = b.package_terms.get(package)?;
+            if term_a != term_b {
+                if differing.is_some() {
+                    return None;
+                }
+                differing = Some((package, term_a.union(term_b)));
+            }
+        }
+        let (package, term) = differing?;
+        let kind = Self::union_kind(&a.kind, &b.kind)?;
+        let mut package_terms = a.package_terms.clone();
+        package_terms.insert(package.clone(), term);
+        Some(Self {
+            package_terms,
+            kind,
+        })
+    }
+
+    /// Kind of the union of two incompatibilities,
+    /// if it can still be described as a single external fact.
+    fn union_kind(a: &Kind<P, V>, b: &Kind<P, V>) -> Option<Kind<P, V>> {
+        match (a, b) {
+            (Kind::NoVersions(p, r), Kind::NoVersions(p_b, r_b)) if p == p_b => {
+                Some(Kind::NoVersions(p.clone(), r.union(r_b)))
+            }
+            (Kind::UnavailableDependencies(p, r), Kind::UnavailableDependencies(p_b, r_b))
+                if p == p_b =>
+            {
+                Some(Kind::UnavailableDependencies(p.clone(), r.union(r_b)))
+            }
+            (
+                Kind::FromDependencyOf(p1, r1, p2, r2),
+                Kind::FromDependencyOf(p1_b, r1_b, p2_b, r2_b),
+            ) if p1 == p1_b && p2 == p2_b && r2 == r2_b => Some(Kind::FromDependencyOf(
+                p1.clone(),
+                r1.union(r1_b),
+                p2.clone(),
+                r2.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The dependent and dependency packages,
+    /// if this incompatibility comes from the dependency of a non-root package on another one.
+    pub fn as_dependency(&self) -> Option<(&P, &P)> {
+        match &self.kind {
+            Kind::FromDependencyOf(p1, _, p2, _) => Some((p1, p2)),
+            _ => None,
+        }
+    }
+
     /// Check if an incompatibility should mark the end of the algorithm
     /// because it satisfies the root package.
     pub fn is_terminal(&self, root_package: &P, root_version: &V) -> bool {
@@ -161,11 +238,97 @@ impl<P: Package, V: Version> Incompatibility<P, V> {
         self.package_terms.get(package)
     }
 
+    /// Check if this incompatibility is redundant given another one,
+    /// meaning that the other one forbids everything this one forbids.
+    /// That is the case when every term of the other incompatibility
+    /// contains the term of this incompatibility for the same package.
+    pub fn is_redundant_given(&self, other: &Self) -> bool {
+        other.package_terms.iter().all(|(package, other_term)| {
+            self.get(package)
+                .is_some_and(|term| term.subset_of(other_term))
+        })
+    }
+
+    /// Check if the term related to a given package contains that version.
+    /// Returns `false` if the package is absent from this incompatibility.
+    pub fn covers(&self, package: &P, version: &V) -> bool {
+        self.get(package).is_some_and(|term| term.contains(version))
+    }
+
     /// Iterate over packages.
     pub fn iter(&self) -> impl Iterator<Item = (&P, &Term<V>)> {
         self.package_terms.iter()
     }
 
+    /// Hash of the packages of this incompatibility,
+    /// independent of the order of its terms.
+    /// Versions are not hashable, so incompatibilities with equal hashes
+    /// must still be compared with [is_same_as](Self::is_same_as).
+    pub fn packages_hash(&self) -> u64 {
+        use std::hash::{BuildHasher, BuildHasherDefault};
+        let hasher = BuildHasherDefault::<rustc_hash::FxHasher>::default();
+        self.package_terms
+            .iter()
+            .map(|(p, _)| hasher.hash_one(p))
+            .fold(0, u64::wrapping_add)
+    }
+
+    /// Check if both incompatibilities have the same terms and the same kind.
+    /// Ranges of the kind must also have the same provenance,
+    /// otherwise merging the two would lose the tags of one of them in reports.
+    pub fn is_same_as(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self
+                .kind_ranges()
+                .into_iter()
+                .zip(other.kind_ranges())
+                .all(|(r1, r2)| r1.provenance().eq(r2.provenance()))
+            && self.package_terms.len() == other.package_terms.len()
+            && self
+                .package_terms
+                .iter()
+                .all(|(p, t)| other.package_terms.get(p) == Some(t))
+    }
+
+    /// Ranges stored in the kind of this incompatibility.
+    fn kind_ranges(&self) -> Vec<&Range<V>> {
+        match &self.kind {
+            Kind::NotRoot(_, _) | Kind::DerivedFrom(_, _) => Vec::new(),
+            Kind::NoVersions(_, r)
+            | Kind::UnavailableDependencies(_, r)
+            | Kind::RootDependency(_, r) => vec![r],
+            Kind::FromDependencyOf(_, r1, _, r2) => vec![r1, r2],
+        }
+    }
+
+    /// Estimate the memory used by this incompatibility, in bytes.
+    ///
+    /// This is the inline size of the incompatibility
+    /// plus the heap allocations of its terms and ranges.
+    /// Heap memory owned by packages or versions themselves is not accounted for.
+    pub fn size_in_bytes(&self) -> usize {
+        let terms_size: usize = self
+            .package_terms
+            .iter()
+            .map(|(_, term)| match term {
+                Term::Positive(r) | Term::Negative(r) => r.heap_size_in_bytes(),
+            })
+            .sum();
+        let kind_size = match &self.kind {
+            Kind::NotRoot(_, _) | Kind::DerivedFrom(_, _) => 0,
+            Kind::NoVersions(_, r)
+            | Kind::UnavailableDependencies(_, r)
+            | Kind::RootDependency(_, r) => r.heap_size_in_bytes(),
+            Kind::FromDependencyOf(_, r1, _, r2) => {
+                r1.heap_size_in_bytes() + r2.heap_size_in_bytes()
+            }
+        };
+        std::mem::size_of::<Self>()
+            + self.package_terms.heap_size_in_bytes()
+            + terms_size
+            + kind_size
+    }
+
     // Reporting ###############################################################
 
     /// Retrieve parent causes if of type DerivedFrom.
@@ -211,6 +374,9 @@ impl<P: Package, V: Version> Incompatibility<P, V> {
                     dep_range.clone(),
                 ))
             }
+            Kind::RootDependency(package, range) => {
+                DerivationTree::External(External::RequiredByRoot(package.clone(), range.clone()))
+            }
         }
     }
 }
@@ -260,6 +426,7 @@ pub mod tests {
     use super::*;
     use crate::term::tests::strategy as term_strat;
     use crate::type_aliases::Map;
+    use crate::version::NumberVersion;
     use proptest::prelude::*;
 
     proptest! {
@@ -292,5 +459,73 @@ pub mod tests {
             assert_eq!(i_resolution.package_terms.as_map(), i3);
         }
 
+        /// An incompatibility covers a version if its term for that package contains it.
+        #[test]
+        fn covers_is_term_contains(t in term_strat(), v in 0..100u32) {
+            let incompat = Incompatibility {
+                package_terms: SmallMap::One([("p", t.clone())]),
+                kind: Kind::UnavailableDependencies("0", Range::any())
+            };
+            assert_eq!(incompat.covers(&"p", &NumberVersion(v)), t.contains(&NumberVersion(v)));
+            assert!(!incompat.covers(&"q", &NumberVersion(v)));
+        }
+
+    }
+
+    #[test]
+    fn provenance_distinguishes_dependencies() {
+        let range = Range::higher_than(2);
+        let tagged = range.clone().with_provenance("a/Cargo.toml:14");
+        let dep = |r| {
+            Incompatibility::<&str, NumberVersion>::from_dependency(
+                "a",
+                NumberVersion(1),
+                (&"b", r),
+                false,
+            )
+        };
+        assert!(dep(&range).is_same_as(&dep(&range)));
+        assert!(dep(&tagged).is_same_as(&dep(&tagged)));
+        assert!(!dep(&range).is_same_as(&dep(&tagged)));
+    }
+
+    #[test]
+    fn try_union_of_dependencies() {
+        let dep_range = Range::between(2, 4);
+        let a1: Incompatibility<&str, NumberVersion> =
+            Incompatibility::from_dependency("a", NumberVersion(1), (&"b", &dep_range), false);
+        let a2 = Incompatibility::from_dependency("a", NumberVersion(2), (&"b", &dep_range), false);
+        let union = Incompatibility::try_union(&a1, &a2).unwrap();
+        assert_eq!(union.get(&"a"), Some(&Term::Positive(Range::between(1, 3))));
+        assert_eq!(union.get(&"b"), Some(&Term::Negative(dep_range)));
+        assert!(
+            matches!(union.kind, Kind::FromDependencyOf(_, r, _, _) if r == Range::between(1, 3))
+        );
+
+        // Terms differing for two packages cannot be merged.
+        let other_dep =
+            Incompatibility::from_dependency("a", NumberVersion(2), (&"b", &Range::any()), false);
+        assert!(Incompatibility::try_union(&a1, &other_dep).is_none());
+        // Incompatibilities on different packages cannot be merged.
+        let c =
+            Incompatibility::from_dependency("a", NumberVersion(2), (&"c", &Range::any()), false);
+        assert!(Incompatibility::try_union(&a1, &c).is_none());
+        // Identical incompatibilities have no term to merge.
+        assert!(Incompatibility::try_union(&a1, &a1).is_none());
+    }
+
+    #[test]
+    fn redundant_incompatibilities() {
+        let dep = |v, r| Incompatibility::from_dependency("a", NumberVersion(v), (&"b", &r), false);
+        let narrow: Incompatibility<&str, NumberVersion> = dep(1, Range::between(2, 3));
+        let wide = dep(1, Range::between(0, 5));
+        // Requiring a wider range of b forbids less.
+        assert!(wide.is_redundant_given(&narrow));
+        assert!(!narrow.is_redundant_given(&wide));
+        assert!(narrow.is_redundant_given(&narrow));
+        // Forbidding a itself is stronger than any dependency of a.
+        let no_a = Incompatibility::no_versions("a", Term::Positive(Range::any()));
+        assert!(narrow.is_redundant_given(&no_a));
+        assert!(!no_a.is_redundant_given(&narrow));
     }
 }
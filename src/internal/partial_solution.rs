@@ -131,6 +131,11 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
         }
     }
 
+    /// Decision level of the last decision.
+    pub fn current_decision_level(&self) -> DecisionLevel {
+        self.current_decision_level
+    }
+
     /// Add a decision.
     pub fn add_decision(&mut self, package: P, version: V) {
         // Check that add_decision is never used in the wrong context.
@@ -148,10 +153,16 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
             }
         }
         self.current_decision_level = self.current_decision_level.increment();
-        let mut pa = self
+        let pa = self
             .package_assignments
             .get_mut(&package)
             .expect("Derivations must already exist");
+        log::debug!(
+            "constraint satisfied: {} {} by version {}",
+            package,
+            pa.assignments_intersection.term(),
+            version
+        );
         pa.highest_decision_level = self.current_decision_level;
         pa.assignments_intersection = AssignmentsIntersection::Decision((
             self.next_global_index,
@@ -178,7 +189,7 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
         self.next_global_index += 1;
         match self.package_assignments.entry(package) {
             Entry::Occupied(mut occupied) => {
-                let mut pa = occupied.get_mut();
+                let pa = occupied.get_mut();
                 pa.highest_decision_level = self.current_decision_level;
                 match &mut pa.assignments_intersection {
                     // Check that add_derivation is never called in the wrong context.
@@ -190,6 +201,11 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
                     }
                 }
                 pa.dated_derivations.push(dated_derivation);
+                log::debug!(
+                    "constraint narrowed: {} {}",
+                    occupied.key(),
+                    occupied.get().assignments_intersection.term()
+                );
             }
             Entry::Vacant(v) => {
                 v.insert(PackageAssignments {
@@ -264,27 +280,51 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
                 // assignment and it would have the "highest_decision_level".
 
                 // Truncate the history.
-                while pa.dated_derivations.last().map(|dd| dd.decision_level) > Some(decision_level)
-                {
-                    pa.dated_derivations.pop();
-                }
+                // Derivations are dated in increasing decision levels,
+                // so the ones to keep can be found with a binary search.
+                let kept = pa
+                    .dated_derivations
+                    .partition_point(|dd| dd.decision_level <= decision_level);
+                pa.dated_derivations.truncate(kept);
                 debug_assert!(!pa.dated_derivations.is_empty());
 
                 // Update highest_decision_level.
                 pa.highest_decision_level = pa.dated_derivations.last().unwrap().decision_level;
 
                 // Recompute the assignments intersection.
-                pa.assignments_intersection = AssignmentsIntersection::Derivations(
-                    pa.dated_derivations
-                        .iter()
-                        .fold(Term::any(), |acc, dated_derivation| {
-                            let term = store[dated_derivation.cause].get(p).unwrap().negate();
-                            acc.intersection(&term)
-                        }),
-                );
+                pa.assignments_intersection =
+                    AssignmentsIntersection::Derivations(pa.derivations_intersection(p, store));
                 true
             }
         });
+        #[cfg(all(debug_assertions, not(feature = "skip-consistency-checks")))]
+        self.recompute_from_scratch(store);
+    }
+
+    /// Recompute the terms intersection of every package from its derivations,
+    /// and check that it is consistent with the incrementally updated one.
+    /// Only compiled with debug assertions, to catch bugs in the incremental updates.
+    /// Debug builds of dependent crates can skip it
+    /// with the `skip-consistency-checks` feature.
+    #[cfg(all(debug_assertions, not(feature = "skip-consistency-checks")))]
+    fn recompute_from_scratch(&self, store: &Arena<Incompatibility<P, V>>) {
+        for (p, pa) in self.package_assignments.iter() {
+            let derivations = pa.derivations_intersection(p, store);
+            match &pa.assignments_intersection {
+                AssignmentsIntersection::Decision((_, version, _)) => {
+                    assert!(
+                        derivations.contains(version),
+                        "decision {} {} is not allowed by the derivations {}",
+                        p,
+                        version,
+                        derivations
+                    );
+                }
+                AssignmentsIntersection::Derivations(term) => {
+                    assert_eq!(term, &derivations, "inconsistent terms for {}", p);
+                }
+            }
+        }
     }
 
     /// We can add the version to the partial solution as a decision
@@ -296,7 +336,7 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
         &mut self,
         package: P,
         version: V,
-        new_incompatibilities: std::ops::Range<IncompId<P, V>>,
+        new_incompatibilities: &[IncompId<P, V>],
         store: &Arena<Incompatibility<P, V>>,
     ) {
         let exact = Term::exact(version.clone());
@@ -312,7 +352,10 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
 
         // Check none of the dependencies (new_incompatibilities)
         // would create a conflict (be satisfied).
-        if store[new_incompatibilities].iter().all(not_satisfied) {
+        if new_incompatibilities
+            .iter()
+            .all(|&id| not_satisfied(&store[id]))
+        {
             log::info!("add_decision: {} @ {}", package, version);
             self.add_decision(package, version);
         } else {
@@ -336,6 +379,16 @@ impl<P: Package, V: Version> PartialSolution<P, V> {
             .map(|pa| pa.assignments_intersection.term())
     }
 
+    /// Retrieve the version chosen for a package, if a decision was made.
+    pub fn decided_version(&self, package: &P) -> Option<&V> {
+        self.package_assignments
+            .get(package)
+            .and_then(|pa| match &pa.assignments_intersection {
+                AssignmentsIntersection::Decision((_, version, _)) => Some(version),
+                AssignmentsIntersection::Derivations(_) => None,
+            })
+    }
+
     /// Figure out if the satisfier and previous satisfier are of different decision levels.
     pub fn satisfier_search(
         &self,
@@ -484,6 +537,22 @@ impl<P: Package, V: Version> PackageAssignments<P, V> {
     }
 }
 
+impl<P: Package, V: Version> PackageAssignments<P, V> {
+    /// Intersection of the terms of all derivations of that package.
+    fn derivations_intersection(
+        &self,
+        package: &P,
+        store: &Arena<Incompatibility<P, V>>,
+    ) -> Term<V> {
+        self.dated_derivations
+            .iter()
+            .fold(Term::any(), |acc, dated_derivation| {
+                let term = store[dated_derivation.cause].get(package).unwrap().negate();
+                acc.intersection(&term)
+            })
+    }
+}
+
 impl<V: Version> AssignmentsIntersection<V> {
     /// Returns the term intersection of all assignments (decision included).
     fn term(&self) -> &Term<V> {
@@ -513,3 +582,65 @@ impl<V: Version> AssignmentsIntersection<V> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::NumberVersion;
+    use proptest::prelude::*;
+
+    /// Partial solution where package 0 gets `derivations[level]` derivations
+    /// at every decision level, and package `level + 1` is then decided.
+    fn build(
+        derivations: &[usize],
+        store: &mut Arena<Incompatibility<u32, NumberVersion>>,
+    ) -> PartialSolution<u32, NumberVersion> {
+        let mut partial_solution = PartialSolution::empty();
+        for (level, &count) in derivations.iter().enumerate() {
+            for k in 0..count {
+                let version = (level * 100 + k) as u32;
+                let cause = store.alloc(Incompatibility::no_versions(
+                    0,
+                    Term::exact(NumberVersion(version)),
+                ));
+                partial_solution.add_derivation(0, cause, store);
+            }
+            let package = level as u32 + 1;
+            let cause = store.alloc(Incompatibility::no_versions(
+                package,
+                Term::exact(NumberVersion(1)),
+            ));
+            partial_solution.add_derivation(package, cause, store);
+            partial_solution.add_decision(package, NumberVersion(0));
+        }
+        partial_solution
+    }
+
+    proptest! {
+        #[test]
+        fn backtrack_to_level(
+            derivations in prop::collection::vec(0..5usize, 1..20),
+            target in 0..20u32,
+        ) {
+            let target = target.min(derivations.len() as u32);
+            let mut store = Arena::new();
+            let mut partial_solution = build(&derivations, &mut store);
+            partial_solution.backtrack(DecisionLevel(target), &store);
+            prop_assert_eq!(partial_solution.current_decision_level(), DecisionLevel(target));
+
+            // The derivations made at levels up to the target are kept.
+            let last_kept = (target as usize + 1).min(derivations.len());
+            let expected: usize = derivations[..last_kept].iter().sum();
+            let kept = partial_solution
+                .package_assignments
+                .get(&0)
+                .map_or(0, |pa| pa.dated_derivations.len());
+            prop_assert_eq!(kept, expected);
+            prop_assert!(partial_solution
+                .package_assignments
+                .values()
+                .flat_map(|pa| pa.dated_derivations.iter())
+                .all(|dd| dd.decision_level <= DecisionLevel(target)));
+        }
+    }
+}
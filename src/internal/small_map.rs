@@ -1,37 +1,39 @@
 use crate::type_aliases::Map;
 use std::hash::Hash;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum SmallMap<K, V> {
+    #[default]
     Empty,
     One([(K, V); 1]),
     Two([(K, V); 2]),
+    Three([(K, V); 3]),
+    Four([(K, V); 4]),
     Flexible(Map<K, V>),
 }
 
 impl<K: PartialEq + Eq + Hash, V> SmallMap<K, V> {
     pub fn get(&self, key: &K) -> Option<&V> {
         match self {
-            Self::Empty => None,
-            Self::One([(k, v)]) if k == key => Some(v),
-            Self::One(_) => None,
-            Self::Two([(k1, v1), _]) if key == k1 => Some(v1),
-            Self::Two([_, (k2, v2)]) if key == k2 => Some(v2),
-            Self::Two(_) => None,
             Self::Flexible(data) => data.get(key),
+            _ => self
+                .inline_entries()
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v),
         }
     }
 
     pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        match self {
-            Self::Empty => None,
-            Self::One([(k, v)]) if k == key => Some(v),
-            Self::One(_) => None,
-            Self::Two([(k1, v1), _]) if key == k1 => Some(v1),
-            Self::Two([_, (k2, v2)]) if key == k2 => Some(v2),
-            Self::Two(_) => None,
-            Self::Flexible(data) => data.get_mut(key),
-        }
+        let entries: &mut [(K, V)] = match self {
+            Self::Empty => &mut [],
+            Self::One(data) => data,
+            Self::Two(data) => data,
+            Self::Three(data) => data,
+            Self::Four(data) => data,
+            Self::Flexible(data) => return data.get_mut(key),
+        };
+        entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
     }
 
     pub fn remove(&mut self, key: &K) -> Option<V> {
@@ -62,36 +64,62 @@ impl<K: PartialEq + Eq + Hash, V> SmallMap<K, V> {
                     Self::Two([(k1, v1), (k2, v2)])
                 }
             }
+            Self::Three([e1, e2, e3]) => {
+                if key == &e1.0 {
+                    out = Some(e1.1);
+                    Self::Two([e2, e3])
+                } else if key == &e2.0 {
+                    out = Some(e2.1);
+                    Self::Two([e1, e3])
+                } else if key == &e3.0 {
+                    out = Some(e3.1);
+                    Self::Two([e1, e2])
+                } else {
+                    out = None;
+                    Self::Three([e1, e2, e3])
+                }
+            }
+            Self::Four([e1, e2, e3, e4]) => {
+                if key == &e1.0 {
+                    out = Some(e1.1);
+                    Self::Three([e2, e3, e4])
+                } else if key == &e2.0 {
+                    out = Some(e2.1);
+                    Self::Three([e1, e3, e4])
+                } else if key == &e3.0 {
+                    out = Some(e3.1);
+                    Self::Three([e1, e2, e4])
+                } else if key == &e4.0 {
+                    out = Some(e4.1);
+                    Self::Three([e1, e2, e3])
+                } else {
+                    out = None;
+                    Self::Four([e1, e2, e3, e4])
+                }
+            }
             Self::Flexible(mut data) => {
                 out = data.remove(key);
-                Self::Flexible(data)
+                Self::from_map(data)
             }
         };
         out
     }
 
     pub fn insert(&mut self, key: K, value: V) {
+        if let Some(v) = self.get_mut(&key) {
+            *v = value;
+            return;
+        }
         *self = match std::mem::take(self) {
             Self::Empty => Self::One([(key, value)]),
-            Self::One([(k, v)]) => {
-                if key == k {
-                    Self::One([(k, value)])
-                } else {
-                    Self::Two([(k, v), (key, value)])
-                }
-            }
-            Self::Two([(k1, v1), (k2, v2)]) => {
-                if key == k1 {
-                    Self::Two([(k1, value), (k2, v2)])
-                } else if key == k2 {
-                    Self::Two([(k1, v1), (k2, value)])
-                } else {
-                    let mut data: Map<K, V> = Map::with_capacity_and_hasher(3, Default::default());
-                    data.insert(key, value);
-                    data.insert(k1, v1);
-                    data.insert(k2, v2);
-                    Self::Flexible(data)
-                }
+            Self::One([e1]) => Self::Two([e1, (key, value)]),
+            Self::Two([e1, e2]) => Self::Three([e1, e2, (key, value)]),
+            Self::Three([e1, e2, e3]) => Self::Four([e1, e2, e3, (key, value)]),
+            Self::Four(entries) => {
+                let mut data: Map<K, V> = Map::with_capacity_and_hasher(5, Default::default());
+                data.extend(entries);
+                data.insert(key, value);
+                Self::Flexible(data)
             }
             Self::Flexible(mut data) => {
                 data.insert(key, value);
@@ -129,40 +157,80 @@ impl<K: Clone + PartialEq + Eq + Hash, V: Clone> SmallMap<K, V> {
     }
 }
 
-impl<K, V> Default for SmallMap<K, V> {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 impl<K, V> SmallMap<K, V> {
     pub fn len(&self) -> usize {
         match self {
             Self::Empty => 0,
             Self::One(_) => 1,
             Self::Two(_) => 2,
+            Self::Three(_) => 3,
+            Self::Four(_) => 4,
             Self::Flexible(data) => data.len(),
         }
     }
+
+    /// Entries stored inline, empty for the [Flexible](Self::Flexible) variant.
+    fn inline_entries(&self) -> &[(K, V)] {
+        match self {
+            Self::Empty | Self::Flexible(_) => &[],
+            Self::One(data) => data,
+            Self::Two(data) => data,
+            Self::Three(data) => data,
+            Self::Four(data) => data,
+        }
+    }
+
+    /// Approximate number of bytes allocated on the heap by this map.
+    pub fn heap_size_in_bytes(&self) -> usize {
+        match self {
+            Self::Flexible(data) => data.capacity() * std::mem::size_of::<(K, V)>(),
+            _ => 0,
+        }
+    }
 }
 
 impl<K: Eq + Hash + Clone, V: Clone> SmallMap<K, V> {
     pub fn as_map(&self) -> Map<K, V> {
+        self.clone().into_map()
+    }
+}
+
+impl<K: Eq + Hash, V> SmallMap<K, V> {
+    /// Consume the small map and move all its entries into a [Map].
+    pub fn into_map(self) -> Map<K, V> {
+        fn collect<K: Eq + Hash, V, const N: usize>(data: [(K, V); N]) -> Map<K, V> {
+            let mut map = Map::with_capacity_and_hasher(N, Default::default());
+            map.extend(data);
+            map
+        }
         match self {
             Self::Empty => Map::default(),
-            Self::One([(k, v)]) => {
-                let mut map = Map::with_capacity_and_hasher(1, Default::default());
-                map.insert(k.clone(), v.clone());
-                map
-            }
-            Self::Two(data) => {
-                let mut map = Map::with_capacity_and_hasher(2, Default::default());
-                for (k, v) in data {
-                    map.insert(k.clone(), v.clone());
-                }
-                map
-            }
-            Self::Flexible(data) => data.clone(),
+            Self::One(data) => collect(data),
+            Self::Two(data) => collect(data),
+            Self::Three(data) => collect(data),
+            Self::Four(data) => collect(data),
+            Self::Flexible(data) => data,
+        }
+    }
+
+    /// Build a small map from a [Map],
+    /// packing its entries back into inline storage if there are four or less.
+    pub fn from_map(map: Map<K, V>) -> Self {
+        if map.len() > 4 {
+            return Self::Flexible(map);
+        }
+        let mut entries = map.into_iter();
+        match (
+            entries.next(),
+            entries.next(),
+            entries.next(),
+            entries.next(),
+        ) {
+            (None, ..) => Self::Empty,
+            (Some(e1), None, ..) => Self::One([e1]),
+            (Some(e1), Some(e2), None, _) => Self::Two([e1, e2]),
+            (Some(e1), Some(e2), Some(e3), None) => Self::Three([e1, e2, e3]),
+            (Some(e1), Some(e2), Some(e3), Some(e4)) => Self::Four([e1, e2, e3, e4]),
         }
     }
 }
@@ -189,7 +257,48 @@ impl<K, V> SmallMap<K, V> {
             Self::Empty => IterSmallMap::Inline([].iter()),
             Self::One(data) => IterSmallMap::Inline(data.iter()),
             Self::Two(data) => IterSmallMap::Inline(data.iter()),
+            Self::Three(data) => IterSmallMap::Inline(data.iter()),
+            Self::Four(data) => IterSmallMap::Inline(data.iter()),
             Self::Flexible(data) => IterSmallMap::Map(data.iter()),
         }
     }
 }
+
+// TESTS #######################################################################
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn into_map_and_back(entries: Vec<(u8, u8)>) {
+            let map: Map<u8, u8> = entries.into_iter().collect();
+            let small_map = SmallMap::from_map(map.clone());
+            assert_eq!(small_map.len(), map.len());
+            assert_eq!(small_map.into_map(), map);
+        }
+
+        #[test]
+        fn insert_and_remove(commands: Vec<(bool, u8, u8)>) {
+            let mut map: Map<u8, u8> = Map::default();
+            let mut small_map = SmallMap::Empty;
+            for (insert, key, value) in commands {
+                // Few keys to often update existing entries.
+                let key = key % 8;
+                if insert {
+                    map.insert(key, value);
+                    small_map.insert(key, value);
+                } else {
+                    assert_eq!(small_map.remove(&key), map.remove(&key));
+                }
+                assert_eq!(small_map.len(), map.len());
+                assert_eq!(small_map.as_map(), map);
+                for k in 0..8 {
+                    assert_eq!(small_map.get(&k), map.get(&k));
+                }
+            }
+        }
+    }
+}
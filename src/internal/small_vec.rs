@@ -1,8 +1,9 @@
 use std::fmt;
 use std::ops::Deref;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum SmallVec<T> {
+    #[default]
     Empty,
     One([T; 1]),
     Two([T; 2]),
@@ -27,6 +28,15 @@ impl<T> SmallVec<T> {
         }
     }
 
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match self {
+            Self::Empty => &mut [],
+            Self::One(v) => v,
+            Self::Two(v) => v,
+            Self::Flexible(v) => v,
+        }
+    }
+
     pub fn push(&mut self, new: T) {
         *self = match std::mem::take(self) {
             Self::Empty => Self::One([new]),
@@ -58,6 +68,34 @@ impl<T> SmallVec<T> {
         }
     }
 
+    /// Shorten the vector, keeping the first `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        if let Self::Flexible(v) = self {
+            v.truncate(len);
+        } else {
+            while self.len() > len {
+                self.pop();
+            }
+        }
+    }
+
+    /// Keep only the elements for which `f` returns true, in place.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        match std::mem::take(self) {
+            Self::Flexible(mut v) => {
+                v.retain(f);
+                *self = Self::Flexible(v);
+            }
+            small => {
+                for t in small {
+                    if f(&t) {
+                        self.push(t);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         if let Self::Flexible(mut v) = std::mem::take(self) {
             v.clear();
@@ -68,11 +106,13 @@ impl<T> SmallVec<T> {
     pub fn iter(&self) -> std::slice::Iter<'_, T> {
         self.as_slice().iter()
     }
-}
 
-impl<T> Default for SmallVec<T> {
-    fn default() -> Self {
-        Self::Empty
+    /// Number of bytes allocated on the heap by this vector.
+    pub fn heap_size_in_bytes(&self) -> usize {
+        match self {
+            Self::Flexible(v) => v.capacity() * std::mem::size_of::<T>(),
+            _ => 0,
+        }
     }
 }
 
@@ -94,6 +134,48 @@ impl<'a, T> IntoIterator for &'a SmallVec<T> {
     }
 }
 
+impl<T> IntoIterator for SmallVec<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::Empty => IntoIter::Inline(None, None),
+            Self::One([v1]) => IntoIter::Inline(Some(v1), None),
+            Self::Two([v1, v2]) => IntoIter::Inline(Some(v1), Some(v2)),
+            Self::Flexible(v) => IntoIter::Heap(v.into_iter()),
+        }
+    }
+}
+
+/// Owning iterator over the elements of a [SmallVec].
+pub enum IntoIter<T> {
+    Inline(Option<T>, Option<T>),
+    Heap(std::vec::IntoIter<T>),
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self {
+            Self::Inline(v1, v2) => v1.take().or_else(|| v2.take()),
+            Self::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match self {
+            Self::Inline(v1, v2) => v1.is_some() as usize + v2.is_some() as usize,
+            Self::Heap(iter) => iter.len(),
+        };
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 impl<T: Eq> Eq for SmallVec<T> {}
 
 impl<T: PartialEq> PartialEq for SmallVec<T> {
@@ -153,5 +235,29 @@ pub mod tests {
                 assert_eq!(v.as_slice(), sv.as_slice());
             }
         }
+
+        #[test]
+        fn truncate(values: Vec<u8>, len in 0..10usize) {
+            let mut v = values.clone();
+            let mut sv = SmallVec::Empty;
+            for i in values {
+                sv.push(i);
+            }
+            v.truncate(len);
+            sv.truncate(len);
+            assert_eq!(v.as_slice(), sv.as_slice());
+        }
+
+        #[test]
+        fn retain(values: Vec<u8>, threshold: u8) {
+            let mut v = values.clone();
+            let mut sv = SmallVec::Empty;
+            for i in values {
+                sv.push(i);
+            }
+            v.retain(|&i| i < threshold);
+            sv.retain(|&i| i < threshold);
+            assert_eq!(v.as_slice(), sv.as_slice());
+        }
     }
 }
@@ -21,10 +21,8 @@
 //!
 //! Our [Version](version::Version) trait requires
 //! [Clone] + [Ord] + [Debug] + [Display](std::fmt::Display)
-//! and also the definition of two methods,
-//! [lowest() -> Self](version::Version::lowest) which returns the lowest version existing,
-//! and [bump(&self) -> Self](version::Version::bump) which returns the next smallest version
-//! strictly higher than the current one.
+//! and also the definition of one method,
+//! [lowest() -> Self](version::Version::lowest) which returns the lowest version existing.
 //! For convenience, this library already provides
 //! two implementations of [Version](version::Version).
 //! The first one is [NumberVersion](version::NumberVersion), basically a newtype for [u32].
@@ -209,8 +207,12 @@
 #![allow(clippy::rc_buffer)]
 #![warn(missing_docs)]
 
+pub mod bit_range_set;
+pub mod discrete_set;
 pub mod error;
 pub mod package;
+#[cfg(any(test, feature = "proptest"))]
+pub mod proptest_support;
 pub mod range;
 pub mod report;
 pub mod solver;
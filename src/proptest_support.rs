@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Strategies generating arbitrary ranges and terms,
+//! for property-based testing with [proptest](mod@proptest) of code using them,
+//! and checks of the laws expected from custom [Version] types.
+//!
+//! This module is only available with the `proptest` feature.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+
+use crate::range::Range;
+use crate::term::Term;
+use crate::version::{NumberVersion, Version};
+
+/// Strategy generating arbitrary ranges, made of up to five intervals,
+/// the last one possibly unbounded.
+pub fn arbitrary_range<V: Version + Arbitrary>() -> impl Strategy<Value = Range<V>> {
+    prop::collection::vec((any::<V>(), any::<bool>()), 0..10).prop_map(Range::from_bounds_list)
+}
+
+/// Strategy generating arbitrary positive or negative terms.
+pub fn arbitrary_term<V: Version + Arbitrary>() -> impl Strategy<Value = Term<V>> {
+    prop_oneof![
+        arbitrary_range().prop_map(Term::Positive),
+        arbitrary_range().prop_map(Term::Negative),
+    ]
+}
+
+/// Check with arbitrary versions that a [Version] type follows the laws
+/// the solver relies on:
+///   - `V::lowest() <= v`,
+///   - `Range::exact(v)` contains `v`, and its complement does not,
+///   - the [successor](Version::successor) of `v`, if any, is higher than `v`.
+///
+/// Call it from the tests of a custom version type.
+/// It panics with the smallest failing version found, if any.
+pub fn verify_version_laws<V: Version + Arbitrary>() {
+    let mut runner = TestRunner::default();
+    let result = runner.run(&any::<V>(), |v| {
+        prop_assert!(V::lowest() <= v, "{} is lower than {}", v, V::lowest());
+        let exact = Range::exact(v.clone());
+        prop_assert!(exact.contains(&v), "{} does not contain {}", exact, v);
+        let complement = exact.negate();
+        prop_assert!(!complement.contains(&v), "{} contains {}", complement, v);
+        if let Some(next) = v.successor() {
+            prop_assert!(v < next, "successor {} is not higher than {}", next, v);
+        }
+        Ok(())
+    });
+    if let Err(error) = result {
+        panic!("version laws are not respected: {}", error);
+    }
+}
+
+impl Arbitrary for NumberVersion {
+    type Parameters = ();
+    type Strategy = prop::strategy::Map<prop::num::u32::Any, fn(u32) -> Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<u32>().prop_map(NumberVersion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_version_laws() {
+        verify_version_laws::<NumberVersion>();
+    }
+}
@@ -13,76 +13,431 @@
 //!  - [higher_than(v)](Range::higher_than): the set defined by `v <= versions`
 //!  - [strictly_lower_than(v)](Range::strictly_lower_than): the set defined by `versions < v`
 //!  - [between(v1, v2)](Range::between): the set defined by `v1 <= versions < v2`
+//!
+//! Variants of those with the other kind of bound are also provided:
+//!  - [higher_than_exclusive(v)](Range::higher_than_exclusive): the set defined by `v < versions`
+//!  - [at_most(v)](Range::at_most): the set defined by `versions <= v`
+//!  - [between_exclusive(v1, v2)](Range::between_exclusive): the set defined by `v1 < versions < v2`,
+//!    also available as [strictly_between](Range::strictly_between)
+//!    and [from_range_exclusive](Range::from_range_exclusive)
+//!
+//! Each bound of an interval is stored as included, excluded or unbounded,
+//! so ranges only rely on versions being ordered.
+//! This makes them exact for dense version spaces, like timestamps or strings,
+//! where there is no version right after another one.
+//! Discrete version types, such as [NumberVersion], provide that next version
+//! with [Version::successor], and their bounds are then always stored
+//! as an included lower bound and an excluded upper bound.
+//! So `1 < v` and `2 <= v` are the same range of [NumberVersion],
+//! while they are different ranges of a version type without successors.
+//!
+//! A range covers a single, totally ordered, dimension of versions.
+//! The product of two ranges, such as "python >= 3.8 and libc >= 2.31",
+//! is generally not a finite union of intervals of pairs of versions.
+//! Such multi-dimensional constraints are better expressed as dependencies
+//! on several packages, one per dimension, each with its own range.
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use thiserror::Error;
 
-use crate::internal::small_vec::SmallVec;
-use crate::version::Version;
+use crate::internal::small_vec::{self, SmallVec};
+use crate::version::{NumberVersion, SemanticVersion, Version};
 
 /// A Range is a set of versions.
-#[derive(Debug, Clone, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///
+/// Ranges made of one or two intervals, such as the [exact](Range::exact)
+/// ranges used for decisions, are stored inline and do not allocate.
+/// A Range is not `Copy` though, since it may hold any number of intervals.
+///
+/// With the `serde` feature, ranges serialize as their list of intervals,
+/// each made of a lower and an upper [Bound],
+/// and can be used with any serde data format,
+/// including compact binary ones such as `postcard`.
+/// Since the number of intervals is not bounded,
+/// there is no maximum size for a serialized range.
+/// Fields can be serialized as readable strings instead with the `serde_string` module.
+///
+/// A range may also carry [provenance](Range::with_provenance) tags,
+/// telling where the constraint comes from.
+/// They are neither compared, hashed nor serialized.
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Range<V: Version> {
     segments: SmallVec<Interval<V>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    provenance: Option<Provenance>,
 }
 
-type Interval<V> = (V, Option<V>);
+/// Interval between a lower and an upper bound.
+/// Intervals of a range are not empty, sorted, and separated by at least one version,
+/// and the lower bound `Included(V::lowest())` is always written [Unbounded].
+type Interval<V> = (Bound<V>, Bound<V>);
+
+/// Tags describing where a range comes from, without duplicates.
+type Provenance = Arc<[Arc<str>]>;
+
+/// Ranges are deserialized like a list of intervals collected into a range,
+/// so intervals may be unsorted, overlapping or empty.
+#[cfg(feature = "serde")]
+impl<'de, V: Version + serde::Deserialize<'de>> serde::Deserialize<'de> for Range<V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let intervals = Vec::<Interval<V>>::deserialize(deserializer)?;
+        Ok(intervals.into_iter().collect())
+    }
+}
+
+/// Ranges are equal when they contain the same segments, whatever their provenance.
+impl<V: Version> PartialEq for Range<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+/// Ranges are hashable when their versions are, to be used as keys of maps,
+/// for example to deduplicate identical dependency requirements.
+/// Equal ranges have the same segments, so they have the same hash.
+impl<V: Version + Hash> Hash for Range<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.segments.as_slice().hash(state);
+    }
+}
 
 // Range building blocks.
 impl<V: Version> Range<V> {
     /// Empty set of versions.
     pub fn none() -> Self {
-        Self {
-            segments: SmallVec::empty(),
-        }
+        Self::from_segments(SmallVec::empty())
     }
 
     /// Set of all possible versions.
     pub fn any() -> Self {
-        Self::higher_than(V::lowest())
+        Self::from_segments(SmallVec::one((Unbounded, Unbounded)))
     }
 
     /// Set containing exactly one version.
     pub fn exact(v: impl Into<V>) -> Self {
         let v = v.into();
-        Self {
-            segments: SmallVec::one((v.clone(), Some(v.bump()))),
-        }
+        Self::from_interval(Included(v.clone()), Included(v))
     }
 
     /// Set of all versions higher or equal to some version.
     pub fn higher_than(v: impl Into<V>) -> Self {
-        Self {
-            segments: SmallVec::one((v.into(), None)),
-        }
+        Self::from_interval(Included(v.into()), Unbounded)
     }
 
     /// Set of all versions strictly lower than some version.
     pub fn strictly_lower_than(v: impl Into<V>) -> Self {
-        let v = v.into();
-        if v == V::lowest() {
-            Self::none()
-        } else {
-            Self {
-                segments: SmallVec::one((V::lowest(), Some(v))),
-            }
-        }
+        Self::from_interval(Unbounded, Excluded(v.into()))
     }
 
     /// Set of all versions comprised between two given versions.
     /// The lower bound is included and the higher bound excluded.
     /// `v1 <= v < v2`.
     pub fn between(v1: impl Into<V>, v2: impl Into<V>) -> Self {
-        let v1 = v1.into();
-        let v2 = v2.into();
-        if v1 < v2 {
-            Self {
-                segments: SmallVec::one((v1, Some(v2))),
+        Self::from_interval(Included(v1.into()), Excluded(v2.into()))
+    }
+
+    /// Set of all versions strictly higher than some version.
+    /// `v < versions`.
+    pub fn higher_than_exclusive(v: impl Into<V>) -> Self {
+        Self::from_interval(Excluded(v.into()), Unbounded)
+    }
+
+    /// Set of all versions lower or equal to some version.
+    /// `versions <= v`.
+    pub fn at_most(v: impl Into<V>) -> Self {
+        Self::from_interval(Unbounded, Included(v.into()))
+    }
+
+    /// Set of all versions comprised between two given versions.
+    /// Both bounds are excluded.
+    /// `v1 < v < v2`.
+    pub fn between_exclusive(v1: impl Into<V>, v2: impl Into<V>) -> Self {
+        Self::from_interval(Excluded(v1.into()), Excluded(v2.into()))
+    }
+
+    /// Open interval `v1 < v < v2`, same as [between_exclusive](Self::between_exclusive).
+    pub fn strictly_between(v1: impl Into<V>, v2: impl Into<V>) -> Self {
+        Self::between_exclusive(v1, v2)
+    }
+
+    /// Open interval `v1 < v < v2`, same as [between_exclusive](Self::between_exclusive).
+    pub fn from_range_exclusive(v1: impl Into<V>, v2: impl Into<V>) -> Self {
+        Self::between_exclusive(v1, v2)
+    }
+
+    /// Set of all versions strictly lower than some version,
+    /// same as [strictly_lower_than](Self::strictly_lower_than).
+    /// `versions < v`.
+    pub fn at_most_exclusive(v: impl Into<V>) -> Self {
+        Self::strictly_lower_than(v)
+    }
+
+    /// Set of all versions strictly higher than some version,
+    /// same as [higher_than_exclusive](Self::higher_than_exclusive).
+    /// `v < versions`.
+    pub fn strictly_higher_than(v: impl Into<V>) -> Self {
+        Self::higher_than_exclusive(v)
+    }
+
+    /// Set of all versions lower or equal to some version,
+    /// same as [at_most](Self::at_most).
+    /// `versions <= v`.
+    pub fn lower_than(v: impl Into<V>) -> Self {
+        Self::at_most(v)
+    }
+
+    /// Set containing exactly the given versions, in any order.
+    /// This is the union of their [exact](Self::exact) ranges,
+    /// computed with a single sort instead of repeated unions.
+    pub fn from_versions(versions: impl IntoIterator<Item = V>) -> Self {
+        versions
+            .into_iter()
+            .map(|v| (Included(v.clone()), Included(v)))
+            .collect()
+    }
+
+    /// Set of all versions between two bounds, each of them included, excluded or unbounded.
+    /// For example `v1 < versions <= v2` is
+    /// `Range::from_bounds(Bound::Excluded(v1), Bound::Included(v2))`.
+    pub fn from_bounds(lower: Bound<V>, upper: Bound<V>) -> Self {
+        Self::from_interval(lower, upper)
+    }
+
+    /// Helper function building a range without provenance from valid segments.
+    fn from_segments(segments: SmallVec<Interval<V>>) -> Self {
+        Self {
+            segments,
+            provenance: None,
+        }
+    }
+
+    /// Helper function building a range of a single interval, which may be empty.
+    fn from_interval(start: Bound<V>, end: Bound<V>) -> Self {
+        match normalize_interval((start, end)) {
+            Some(interval) => Self::from_segments(SmallVec::one(interval)),
+            None => Self::none(),
+        }
+    }
+}
+
+// Bounds.
+
+/// Write the bounds of an interval in a single way, or return [None] if it is empty.
+/// Bounds of versions with a [successor](Version::successor) are written
+/// as an included lower bound and an excluded upper bound,
+/// and the lower bound `Included(V::lowest())` is written [Unbounded].
+fn normalize_interval<V: Version>((start, end): Interval<V>) -> Option<Interval<V>> {
+    let start = match start {
+        Excluded(v) => v.successor().map_or(Excluded(v), Included),
+        start => start,
+    };
+    let start = match start {
+        Included(v) if v == V::lowest() => Unbounded,
+        start => start,
+    };
+    let end = match end {
+        Included(v) => v.successor().map_or(Included(v), Excluded),
+        end => end,
+    };
+    if is_valid_interval(&start, &end) {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+/// Check that an interval contains at least one version.
+fn is_valid_interval<V: Version>(start: &Bound<V>, end: &Bound<V>) -> bool {
+    match (start, end) {
+        (Included(s), Included(e)) => s <= e,
+        (Included(s), Excluded(e)) | (Excluded(s), Included(e)) | (Excluded(s), Excluded(e)) => {
+            s < e
+        }
+        (Unbounded, Excluded(e)) => e > &V::lowest(),
+        _ => true,
+    }
+}
+
+/// Order lower bounds by the versions they let in,
+/// so that [Unbounded] comes first and `Included(v)` comes before `Excluded(v)`.
+fn cmp_start<V: Ord>(a: &Bound<V>, b: &Bound<V>) -> Ordering {
+    match (a, b) {
+        (Unbounded, Unbounded) => Ordering::Equal,
+        (Unbounded, _) => Ordering::Less,
+        (_, Unbounded) => Ordering::Greater,
+        (Included(a), Included(b)) | (Excluded(a), Excluded(b)) => a.cmp(b),
+        (Included(a), Excluded(b)) => a.cmp(b).then(Ordering::Less),
+        (Excluded(a), Included(b)) => a.cmp(b).then(Ordering::Greater),
+    }
+}
+
+/// Order upper bounds by the versions they let in,
+/// so that [Unbounded] comes last and `Excluded(v)` comes before `Included(v)`.
+fn cmp_end<V: Ord>(a: &Bound<V>, b: &Bound<V>) -> Ordering {
+    match (a, b) {
+        (Unbounded, Unbounded) => Ordering::Equal,
+        (Unbounded, _) => Ordering::Greater,
+        (_, Unbounded) => Ordering::Less,
+        (Included(a), Included(b)) | (Excluded(a), Excluded(b)) => a.cmp(b),
+        (Included(a), Excluded(b)) => a.cmp(b).then(Ordering::Greater),
+        (Excluded(a), Included(b)) => a.cmp(b).then(Ordering::Less),
+    }
+}
+
+/// Check that all the versions of an interval ending at `end`
+/// are lower than all the versions of an interval starting at `start`.
+fn end_before_start<V: Ord>(end: &Bound<V>, start: &Bound<V>) -> bool {
+    match (end, start) {
+        (Included(e), Included(s)) => e < s,
+        (Included(e), Excluded(s)) | (Excluded(e), Included(s)) | (Excluded(e), Excluded(s)) => {
+            e <= s
+        }
+        _ => false,
+    }
+}
+
+/// Check that there is a version between an interval ending at `end`
+/// and an interval starting at `start`, so that they cannot be merged.
+fn is_separated<V: Ord>(end: &Bound<V>, start: &Bound<V>) -> bool {
+    match (end, start) {
+        (Excluded(e), Excluded(s)) => e <= s,
+        (Included(e), Included(s)) | (Included(e), Excluded(s)) | (Excluded(e), Included(s)) => {
+            e < s
+        }
+        _ => false,
+    }
+}
+
+/// Check that a version is not lower than a lower bound.
+fn start_contains<V: Ord>(start: &Bound<V>, version: &V) -> bool {
+    match start {
+        Included(s) => s <= version,
+        Excluded(s) => s < version,
+        Unbounded => true,
+    }
+}
+
+/// Check that a version is not higher than an upper bound.
+fn end_contains<V: Ord>(end: &Bound<V>, version: &V) -> bool {
+    match end {
+        Included(e) => version <= e,
+        Excluded(e) => version < e,
+        Unbounded => true,
+    }
+}
+
+/// Lower bound of the versions right after an upper bound,
+/// or [None] if the upper bound is [Unbounded].
+fn start_after<V: Clone>(end: &Bound<V>) -> Option<Bound<V>> {
+    match end {
+        Included(e) => Some(Excluded(e.clone())),
+        Excluded(e) => Some(Included(e.clone())),
+        Unbounded => None,
+    }
+}
+
+/// Upper bound of the versions right before a lower bound,
+/// or [None] if the lower bound is [Unbounded].
+fn end_before<V: Clone>(start: &Bound<V>) -> Option<Bound<V>> {
+    match start {
+        Included(s) => Some(Excluded(s.clone())),
+        Excluded(s) => Some(Included(s.clone())),
+        Unbounded => None,
+    }
+}
+
+fn map_bound<V, U>(bound: Bound<V>, f: impl FnOnce(V) -> U) -> Bound<U> {
+    match bound {
+        Included(v) => Included(f(v)),
+        Excluded(v) => Excluded(f(v)),
+        Unbounded => Unbounded,
+    }
+}
+
+/// Position where a bound cuts the line of versions,
+/// used to walk the bounds of several ranges in order.
+#[derive(Debug, PartialEq, Eq)]
+enum Cut<'a, V> {
+    /// Before all versions.
+    Lowest,
+    /// Right before a version.
+    Before(&'a V),
+    /// Right after a version.
+    After(&'a V),
+    /// After all versions.
+    Highest,
+}
+
+impl<'a, V> Clone for Cut<'a, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, V> Copy for Cut<'a, V> {}
+
+impl<'a, V: Ord> Ord for Cut<'a, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn key<'b, V>(cut: &Cut<'b, V>) -> (u8, Option<&'b V>, u8) {
+            match *cut {
+                Cut::Lowest => (0, None, 0),
+                Cut::Before(v) => (1, Some(v), 0),
+                Cut::After(v) => (1, Some(v), 1),
+                Cut::Highest => (2, None, 0),
             }
-        } else {
-            Self::none()
+        }
+        key(self).cmp(&key(other))
+    }
+}
+
+impl<'a, V: Ord> PartialOrd for Cut<'a, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, V: Clone> Cut<'a, V> {
+    fn of_start(start: &'a Bound<V>) -> Self {
+        match start {
+            Unbounded => Cut::Lowest,
+            Included(v) => Cut::Before(v),
+            Excluded(v) => Cut::After(v),
+        }
+    }
+
+    fn of_end(end: &'a Bound<V>) -> Self {
+        match end {
+            Included(v) => Cut::After(v),
+            Excluded(v) => Cut::Before(v),
+            Unbounded => Cut::Highest,
+        }
+    }
+
+    fn to_start(self) -> Bound<V> {
+        match self {
+            Cut::Lowest => Unbounded,
+            Cut::Before(v) => Included(v.clone()),
+            Cut::After(v) => Excluded(v.clone()),
+            Cut::Highest => unreachable!("no segment starts after all versions"),
+        }
+    }
+
+    fn to_end(self) -> Bound<V> {
+        match self {
+            Cut::Lowest => unreachable!("no segment ends before all versions"),
+            Cut::Before(v) => Excluded(v.clone()),
+            Cut::After(v) => Included(v.clone()),
+            Cut::Highest => Unbounded,
         }
     }
 }
@@ -93,234 +448,1612 @@ impl<V: Version> Range<V> {
 
     /// Compute the complement set of versions.
     pub fn negate(&self) -> Self {
-        match self.segments.first() {
-            None => Self::any(), // Complement of ∅  is *
-
-            // First high bound is +∞
-            Some((v, None)) => {
-                // Complement of * is ∅
-                if v == &V::lowest() {
-                    Self::none()
-                // Complement of "v <= _" is "_ < v"
+        let mut segments = SmallVec::empty();
+        for interval in self.complement_segments_iter() {
+            segments.push(interval);
+        }
+        self.with_segments(segments)
+    }
+
+    /// Iterate over the segments of the complement set of versions,
+    /// without computing the whole complement.
+    pub fn complement_segments_iter(&self) -> impl Iterator<Item = Interval<V>> + '_ {
+        let mut start = Some(Unbounded);
+        let mut segments = self.segments.iter();
+        std::iter::from_fn(move || loop {
+            let current_start = start.take()?;
+            match segments.next() {
+                Some((s, e)) => {
+                    start = start_after(e);
+                    // There is nothing before a segment starting without lower bound.
+                    if let Some(end) = end_before(s) {
+                        return Some((current_start, end));
+                    }
+                }
+                None => return Some((current_start, Unbounded)),
+            }
+        })
+    }
+
+    /// Compute the complement of this set of versions within a universe of versions,
+    /// meaning the versions of the universe that are not in this set.
+    /// Unlike [negate](Self::negate), the result has no upper bound
+    /// only if the universe has none.
+    pub fn complement_within(&self, universe: &Self) -> Self {
+        universe.difference(self)
+    }
+
+    // Union and intersection ##################################################
+
+    /// Compute the union of two sets of versions.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut segments = SmallVec::empty();
+        let mut left_iter = self.segments.iter().peekable();
+        let mut right_iter = other.segments.iter().peekable();
+        // Segment being built, that may still be extended by the next ones.
+        let mut current: Option<Interval<V>> = None;
+        loop {
+            // Walk both lists of segments at once, by increasing start.
+            let (start, end) = match (left_iter.peek(), right_iter.peek()) {
+                (Some((l1, _)), Some((r1, _))) if cmp_start(l1, r1) != Ordering::Greater => {
+                    left_iter.next().unwrap()
+                }
+                (_, Some(_)) => right_iter.next().unwrap(),
+                (Some(_), None) => left_iter.next().unwrap(),
+                (None, None) => break,
+            };
+            match current.as_mut() {
+                None => current = Some((start.clone(), end.clone())),
+                // The current segment is infinite so it contains all the next ones.
+                Some((_, Unbounded)) => break,
+                // The segments overlap or are contiguous, they are merged.
+                Some((_, current_end)) if !is_separated(current_end, start) => {
+                    if cmp_end(end, current_end) == Ordering::Greater {
+                        *current_end = end.clone();
+                    }
+                }
+                // The segments are disjoint.
+                Some(_) => {
+                    segments.push(current.take().unwrap());
+                    current = Some((start.clone(), end.clone()));
+                }
+            }
+        }
+        if let Some(last) = current {
+            segments.push(last);
+        }
+        Self {
+            segments,
+            provenance: merge_provenance(&self.provenance, &other.provenance),
+        }
+    }
+
+    /// Compute the intersection of two sets of versions.
+    /// The result keeps the [provenance](Self::with_provenance) of both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut segments = SmallVec::empty();
+        self.for_each_intersection_segment(other, |start, end| {
+            segments.push((start.clone(), end.clone()))
+        });
+        Self {
+            segments,
+            provenance: merge_provenance(&self.provenance, &other.provenance),
+        }
+    }
+
+    /// Intersect this set of versions with another one, in place.
+    /// Intersecting with a single interval, or with a superset of this one,
+    /// reuses the existing segments instead of allocating new ones.
+    pub fn intersect_assign(&mut self, other: &Self) {
+        match other.segments.as_slice() {
+            // Intersecting with a single interval only removes and shortens segments.
+            [(start, end)] => {
+                self.segments
+                    .retain(|(s, e)| !end_before_start(e, start) && !end_before_start(end, s));
+                let segments = self.segments.as_mut_slice();
+                if let Some((s, _)) = segments.first_mut() {
+                    if cmp_start(s, start) == Ordering::Less {
+                        *s = start.clone();
+                    }
+                }
+                if let Some((_, e)) = segments.last_mut() {
+                    if cmp_end(e, end) == Ordering::Greater {
+                        *e = end.clone();
+                    }
+                }
+            }
+            _ if self.is_subset_of(other) => {}
+            _ => {
+                self.segments = self.intersection(other).segments;
+            }
+        }
+        self.provenance = merge_provenance(&self.provenance, &other.provenance);
+    }
+
+    /// Add the versions of another set to this one, in place.
+    /// Nothing is allocated if the other set is already included in this one.
+    pub fn union_assign(&mut self, other: &Self) {
+        if !other.is_subset_of(self) {
+            *self = self.union(other);
+        }
+    }
+
+    /// Compute the union of any number of sets of versions.
+    /// Unlike folding [union](Self::union), which copies the accumulated segments
+    /// at every step, all the sets are merged at once.
+    pub fn union_all<'a>(ranges: impl IntoIterator<Item = &'a Self>) -> Self
+    where
+        V: 'a,
+    {
+        Self::merge_all(ranges.into_iter().collect(), |count, _| count > 0)
+    }
+
+    /// Compute the intersection of any number of sets of versions.
+    /// The intersection of no set at all is the set of all versions.
+    /// Unlike folding [intersection](Self::intersection), which copies the accumulated segments
+    /// at every step, all the sets are merged at once.
+    pub fn intersection_all<'a>(ranges: impl IntoIterator<Item = &'a Self>) -> Self
+    where
+        V: 'a,
+    {
+        let ranges: Vec<_> = ranges.into_iter().collect();
+        if ranges.is_empty() {
+            return Self::any();
+        }
+        Self::merge_all(ranges, |count, total| count == total)
+    }
+
+    /// Helper function walking the bounds of all the ranges at once, by increasing version,
+    /// while counting how many ranges contain the versions in between.
+    /// The result contains the versions for which `keep(count, ranges.len())` holds.
+    fn merge_all(ranges: Vec<&Self>, keep: impl Fn(usize, usize) -> bool) -> Self {
+        // The n-th bound of a range is the start of its segment n / 2 if n is even,
+        // and the end of that segment otherwise.
+        fn cut<V: Version>(range: &Range<V>, n: usize) -> Option<Cut<'_, V>> {
+            let (start, end) = range.segments.get(n / 2)?;
+            match n % 2 {
+                0 => Some(Cut::of_start(start)),
+                _ => Some(Cut::of_end(end)),
+            }
+        }
+        // Min-heap of the next bound of each range, with the index of that range.
+        let mut heap: BinaryHeap<Reverse<(Cut<'_, V>, usize)>> = ranges
+            .iter()
+            .enumerate()
+            .filter_map(|(i, range)| Some(Reverse((cut(range, 0)?, i))))
+            .collect();
+        let mut positions = vec![0; ranges.len()];
+        let mut segments = SmallVec::empty();
+        let mut count = 0;
+        let mut current: Option<Cut<'_, V>> = None;
+        while let Some(&Reverse((position, _))) = heap.peek() {
+            // Process all the bounds at this position before deciding,
+            // so that contiguous segments of different ranges are merged.
+            while let Some(&Reverse((c, i))) = heap.peek() {
+                if c != position {
+                    break;
+                }
+                heap.pop();
+                match positions[i] % 2 {
+                    0 => count += 1,
+                    _ => count -= 1,
+                }
+                positions[i] += 1;
+                if let Some(next) = cut(ranges[i], positions[i]) {
+                    heap.push(Reverse((next, i)));
+                }
+            }
+            match (keep(count, ranges.len()), current) {
+                (true, None) => current = Some(position),
+                (false, Some(start)) => {
+                    segments.push((start.to_start(), position.to_end()));
+                    current = None;
+                }
+                _ => {}
+            }
+        }
+        let provenance = ranges.iter().fold(None, |provenance, range| {
+            merge_provenance(&provenance, &range.provenance)
+        });
+        Self {
+            segments,
+            provenance,
+        }
+    }
+
+    /// Count the number of segments in the intersection of two sets of versions,
+    /// without allocating the intersection itself.
+    /// This is equal to the number of segments of `self.intersection(other)`.
+    pub fn intersection_count(&self, other: &Self) -> usize {
+        let mut count = 0;
+        self.for_each_intersection_segment(other, |_, _| count += 1);
+        count
+    }
+
+    /// Compute the set of versions in this set but not in the other one.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self && !in_other)
+    }
+
+    /// Compute the set of versions in exactly one of the two sets.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self != in_other)
+    }
+
+    /// Helper function walking the bounds of two sets in a single pass, by increasing version,
+    /// and keeping the versions for which `keep(in_self, in_other)` holds.
+    /// Versions in none of the sets must not be kept.
+    fn combine(&self, other: &Self, keep: impl Fn(bool, bool) -> bool) -> Self {
+        // Membership flips at each bound of the segments of a set.
+        fn cuts<V: Version>(range: &Range<V>) -> impl Iterator<Item = Cut<'_, V>> {
+            range.segments.iter().flat_map(|(start, end)| {
+                std::iter::once(Cut::of_start(start)).chain(std::iter::once(Cut::of_end(end)))
+            })
+        }
+        let mut left_iter = cuts(self).peekable();
+        let mut right_iter = cuts(other).peekable();
+        let (mut in_left, mut in_right) = (false, false);
+        let mut segments = SmallVec::empty();
+        // Start of the segment being built.
+        let mut current: Option<Cut<'_, V>> = None;
+        loop {
+            let position = match (left_iter.peek(), right_iter.peek()) {
+                (Some(&l), Some(&r)) => std::cmp::min(l, r),
+                (Some(&l), None) => l,
+                (None, Some(&r)) => r,
+                (None, None) => break,
+            };
+            // Both sets may flip at once.
+            if left_iter.next_if_eq(&position).is_some() {
+                in_left = !in_left;
+            }
+            if right_iter.next_if_eq(&position).is_some() {
+                in_right = !in_right;
+            }
+            match (keep(in_left, in_right), current) {
+                (true, None) => current = Some(position),
+                (false, Some(start)) => {
+                    segments.push((start.to_start(), position.to_end()));
+                    current = None;
+                }
+                _ => {}
+            }
+        }
+        Self {
+            segments,
+            provenance: merge_provenance(&self.provenance, &other.provenance),
+        }
+    }
+
+    /// Check if two sets of versions have at least one version in common,
+    /// without computing their intersection.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mut left_iter = self.segments.iter();
+        let mut right_iter = other.segments.iter();
+        let mut left = left_iter.next();
+        let mut right = right_iter.next();
+        while let (Some((l1, l2)), Some((r1, r2))) = (left, right) {
+            if end_before_start(l2, r1) {
+                // The left interval ends before the right one starts.
+                left = left_iter.next();
+            } else if end_before_start(r2, l1) {
+                // The right interval ends before the left one starts.
+                right = right_iter.next();
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check if two sets of versions have no version in common,
+    /// without computing their intersection.
+    /// This is the opposite of [overlaps](Self::overlaps).
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.overlaps(other)
+    }
+
+    /// Check if all versions of this set are also in the other one,
+    /// without computing their intersection.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        let mut other_iter = other.segments.iter().peekable();
+        'segments: for (start, end) in &self.segments {
+            // Segments of the other set are disjoint and not contiguous,
+            // so this segment must be contained in a single one of them.
+            while let Some((o1, o2)) = other_iter.peek() {
+                if end_before_start(o2, start) {
+                    other_iter.next();
+                    continue;
+                }
+                let contained = cmp_start(o1, start) != Ordering::Greater
+                    && cmp_end(end, o2) != Ordering::Greater;
+                if contained {
+                    continue 'segments;
+                }
+                return false;
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Intersection of two sets of versions that is only computed when needed.
+    /// Checking if it is empty or equal to another range does not allocate.
+    pub fn lazy_intersection<'a>(&'a self, other: &'a Self) -> LazyIntersection<'a, V> {
+        LazyIntersection {
+            left: self,
+            right: other,
+        }
+    }
+
+    /// Helper function walking both lists of segments at once,
+    /// and calling `f` on every segment of the intersection, in increasing order.
+    fn for_each_intersection_segment<'a>(
+        &'a self,
+        other: &'a Self,
+        f: impl FnMut(&'a Bound<V>, &'a Bound<V>),
+    ) {
+        Self::walk_intersection(&self.segments, &other.segments, f)
+    }
+
+    /// Same as [for_each_intersection_segment](Self::for_each_intersection_segment),
+    /// on lists of segments.
+    fn walk_intersection<'a>(
+        left: &'a [Interval<V>],
+        right: &'a [Interval<V>],
+        mut f: impl FnMut(&'a Bound<V>, &'a Bound<V>),
+    ) {
+        let mut left_iter = left.iter();
+        let mut right_iter = right.iter();
+        let mut left = left_iter.next();
+        let mut right = right_iter.next();
+        while let (Some((l1, l2)), Some((r1, r2))) = (left, right) {
+            // The intersection starts at the highest start and ends at the lowest end.
+            let start = std::cmp::max_by(l1, r1, |a, b| cmp_start(a, b));
+            let end = std::cmp::min_by(l2, r2, |a, b| cmp_end(a, b));
+            if is_valid_interval(start, end) {
+                f(start, end);
+            }
+            // Progress on the side ending first, it cannot intersect anything else.
+            match cmp_end(l2, r2) {
+                Ordering::Less => left = left_iter.next(),
+                Ordering::Greater => right = right_iter.next(),
+                Ordering::Equal => {
+                    left = left_iter.next();
+                    right = right_iter.next();
+                }
+            }
+        }
+    }
+}
+
+// Other useful functions.
+impl<V: Version> Range<V> {
+    /// Check if a range contains a given version.
+    pub fn contains(&self, version: &V) -> bool {
+        for (start, end) in &self.segments {
+            if !start_contains(start, version) {
+                return false;
+            }
+            if end_contains(end, version) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check if the range contains each of the given versions.
+    /// When versions are sorted in increasing order, they are checked in a single pass
+    /// over the segments of the range, which is faster than calling
+    /// [contains](Self::contains) for each version.
+    /// Unsorted versions give the same results, only slower.
+    pub fn contains_many<'a>(
+        &'a self,
+        versions: impl Iterator<Item = &'a V> + 'a,
+    ) -> impl Iterator<Item = bool> + 'a {
+        // Index of the first segment that may contain the current version.
+        let mut index = 0;
+        let mut previous: Option<&V> = None;
+        versions.map(move |version| {
+            if previous.is_some_and(|previous| version < previous) {
+                index = 0;
+            }
+            previous = Some(version);
+            while let Some((_, end)) = self.segments.get(index) {
+                if end_contains(end, version) {
+                    break;
+                }
+                index += 1;
+            }
+            matches!(self.segments.get(index), Some((start, _)) if start_contains(start, version))
+        })
+    }
+
+    /// Check if the range contains no version,
+    /// without comparing it to [none](Self::none).
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Check if the range contains every version,
+    /// without comparing it to [any](Self::any).
+    pub fn is_full(&self) -> bool {
+        matches!(self.segments.as_slice(), [(Unbounded, Unbounded)])
+    }
+
+    /// Check if the range is not empty and excludes the lowest version.
+    pub fn is_lower_bounded(&self) -> bool {
+        !matches!(self.segments.first(), None | Some((Unbounded, _)))
+    }
+
+    /// Check if the range is not empty and has a highest version.
+    pub fn is_upper_bounded(&self) -> bool {
+        !matches!(self.segments.last(), None | Some((_, Unbounded)))
+    }
+
+    /// Iterate over the segments composing this range, sorted and disjoint,
+    /// as pairs of lower and upper bounds.
+    /// A range containing the lowest version starts with an [Unbounded] lower bound.
+    pub fn iter_segments(&self) -> RangeSegments<'_, V> {
+        RangeSegments {
+            iter: self.segments.iter(),
+        }
+    }
+
+    /// Same as [iter_segments](Self::iter_segments), but consuming the range.
+    pub fn into_segments(self) -> IntoRangeSegments<V> {
+        IntoRangeSegments {
+            iter: self.segments.into_iter(),
+        }
+    }
+
+    /// Return the lowest version in the range (if there is one).
+    /// There is none if the range is empty,
+    /// or if its lower bound is excluded and versions have no [successor](Version::successor).
+    pub fn lowest_version(&self) -> Option<V> {
+        match self.segments.first()? {
+            (Included(start), _) => Some(start.clone()),
+            (Excluded(_), _) => None,
+            (Unbounded, _) => Some(V::lowest()),
+        }
+    }
+
+    /// Convert this range to another version type,
+    /// by mapping the bounds of every segment.
+    /// An unbounded start stands for [V::lowest()](Version::lowest),
+    /// which is mapped like any other version.
+    /// The mapping must be strictly monotone, which is acknowledged
+    /// by wrapping it in [Monotone].
+    pub fn apply_version_mapping<U: Version, M: MonotoneMap<V, U>>(&self, mapping: M) -> Range<U> {
+        let mut intervals: Vec<Interval<U>> = Vec::with_capacity(self.segments.len());
+        for (start, end) in &self.segments {
+            let start = match start {
+                Unbounded => Included(mapping.map(V::lowest())),
+                start => map_bound(start.clone(), |v| mapping.map(v)),
+            };
+            let end = map_bound(end.clone(), |v| mapping.map(v));
+            debug_assert!(
+                is_valid_interval(&start, &end),
+                "version mapping is not monotone"
+            );
+            if let Some((_, prev_end)) = intervals.last() {
+                debug_assert!(
+                    end_before_start(prev_end, &start),
+                    "version mapping is not monotone"
+                );
+            }
+            intervals.push((start, end));
+        }
+        // Separated versions may have become contiguous in the other version type.
+        let mut range: Range<U> = intervals.into_iter().collect();
+        range.provenance = self.provenance.clone();
+        range
+    }
+
+    /// The only version of the range, if it contains exactly one.
+    fn exact_version(&self) -> Option<V> {
+        let (start, end) = match self.segments.as_slice() {
+            [(start, end)] => (start, end),
+            _ => return None,
+        };
+        let start = match start {
+            Included(start) => start.clone(),
+            Unbounded => V::lowest(),
+            Excluded(_) => return None,
+        };
+        let exact = match end {
+            Included(end) => end == &start,
+            Excluded(end) => start.successor().as_ref() == Some(end),
+            Unbounded => false,
+        };
+        if exact {
+            Some(start)
+        } else {
+            None
+        }
+    }
+
+    /// Return the upper bound of the range (if there is one).
+    /// The outer [None] means that the range is empty,
+    /// while [Unbounded] means that the range has no upper bound.
+    pub fn highest_version(&self) -> Option<Bound<V>> {
+        self.segments.last().map(|(_, end)| end).cloned()
+    }
+
+    /// Coarsest range selecting the same versions as this one among the `available` ones,
+    /// which must be sorted in increasing order.
+    /// Bounds are moved to the closest available versions that are not in this range,
+    /// and removed when there is none.
+    /// For example with available versions 1, 3 and 5,
+    /// the range `2 <= v < 4` becomes `1 < v < 5`, and `3 <= v < 4` becomes `1 < v < 5` too,
+    /// which is `2 <= v < 5` for versions with a [successor](Version::successor).
+    pub fn simplify(&self, available: &[V]) -> Self {
+        debug_assert!(
+            available.windows(2).all(|pair| pair[0] < pair[1]),
+            "available versions are not sorted"
+        );
+        let mut segments = SmallVec::empty();
+        let mut segments_iter = self.segments.iter().peekable();
+        // Start of the segment being built, while available versions are in this range.
+        let mut current: Option<Bound<V>> = None;
+        // Last available version seen that is not in this range.
+        let mut last_excluded: Option<&V> = None;
+        for version in available {
+            while let Some((_, end)) = segments_iter.peek() {
+                if end_contains(end, version) {
+                    break;
+                }
+                segments_iter.next();
+            }
+            let contained = segments_iter
+                .peek()
+                .is_some_and(|(start, _)| start_contains(start, version));
+            if contained {
+                if current.is_none() {
+                    current = Some(last_excluded.map_or(Unbounded, |v| Excluded(v.clone())));
+                }
+            } else {
+                if let Some(start) = current.take() {
+                    if let Some(interval) = normalize_interval((start, Excluded(version.clone()))) {
+                        segments.push(interval);
+                    }
+                }
+                last_excluded = Some(version);
+            }
+        }
+        if let Some(start) = current {
+            if let Some(interval) = normalize_interval((start, Unbounded)) {
+                segments.push(interval);
+            }
+        }
+        self.with_segments(segments)
+    }
+
+    /// Return the smallest interval containing the whole range (if it is not empty),
+    /// as a pair of lower and upper bounds.
+    pub fn bounding_interval(&self) -> Option<(Bound<V>, Bound<V>)> {
+        let (start, _) = self.segments.first()?;
+        let (_, end) = self.segments.last()?;
+        Some((start.clone(), end.clone()))
+    }
+
+    /// Number of bytes allocated on the heap to store the segments of this range.
+    pub(crate) fn heap_size_in_bytes(&self) -> usize {
+        self.segments.heap_size_in_bytes()
+    }
+}
+
+/// Strictly monotone mapping between two version types,
+/// used by [apply_version_mapping](Range::apply_version_mapping).
+/// This trait is sealed, the only implementation is [Monotone].
+pub trait MonotoneMap<V, U>: sealed::Sealed {
+    /// Map a version.
+    fn map(&self, version: V) -> U;
+}
+
+/// Wrapper around a function asserting that it is strictly monotone,
+/// meaning that `v1 < v2` implies `f(v1) < f(v2)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Monotone<F>(pub F);
+
+impl<V, U, F: Fn(V) -> U> MonotoneMap<V, U> for Monotone<F> {
+    fn map(&self, version: V) -> U {
+        (self.0)(version)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl<F> Sealed for super::Monotone<F> {}
+}
+
+/// Iterator over the segments of a range,
+/// created with [iter_segments](Range::iter_segments).
+#[derive(Debug, Clone)]
+pub struct RangeSegments<'a, V: Version> {
+    iter: std::slice::Iter<'a, Interval<V>>,
+}
+
+impl<'a, V: Version> Iterator for RangeSegments<'a, V> {
+    type Item = (&'a Bound<V>, &'a Bound<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(start, end)| (start, end))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, V: Version> ExactSizeIterator for RangeSegments<'a, V> {}
+
+/// Owning iterator over the segments of a range,
+/// created with [into_segments](Range::into_segments).
+pub struct IntoRangeSegments<V: Version> {
+    iter: small_vec::IntoIter<Interval<V>>,
+}
+
+impl<V: Version> Iterator for IntoRangeSegments<V> {
+    type Item = (Bound<V>, Bound<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<V: Version> ExactSizeIterator for IntoRangeSegments<V> {}
+
+/// Intersection of two ranges that is not computed yet.
+/// Created with [lazy_intersection](Range::lazy_intersection).
+#[derive(Debug, Clone, Copy)]
+pub struct LazyIntersection<'a, V: Version> {
+    left: &'a Range<V>,
+    right: &'a Range<V>,
+}
+
+impl<'a, V: Version> LazyIntersection<'a, V> {
+    /// Check if the intersection is empty, without computing it.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_disjoint(self.right)
+    }
+
+    /// Compute the intersection.
+    pub fn compute(&self) -> Range<V> {
+        self.left.intersection(self.right)
+    }
+}
+
+impl<'a, V: Version> PartialEq<Range<V>> for LazyIntersection<'a, V> {
+    fn eq(&self, other: &Range<V>) -> bool {
+        let mut index = 0;
+        let mut equal = true;
+        self.left
+            .for_each_intersection_segment(self.right, |start, end| {
+                match other.segments.get(index) {
+                    Some((s, e)) if s == start && e == end => {}
+                    _ => equal = false,
+                }
+                index += 1;
+            });
+        equal && index == other.segments.len()
+    }
+}
+
+// Provenance.
+impl<V: Version> Range<V> {
+    /// Tag this range with where it comes from,
+    /// such as `"foo 1.2.0's manifest line 14"`.
+    /// Tags are kept by [negate](Self::negate), [simplify](Self::simplify)
+    /// and version mappings, and the tags of both sets are kept
+    /// by [intersection](Self::intersection), [union](Self::union) and [difference](Self::difference).
+    /// They end up in the ranges of the derivation tree,
+    /// and reports mention them after the dependencies they constrain.
+    pub fn with_provenance(mut self, tag: impl Into<Arc<str>>) -> Self {
+        let tag: Provenance = Arc::new([tag.into()]);
+        self.provenance = merge_provenance(&self.provenance, &Some(tag));
+        self
+    }
+
+    /// Iterate over the provenance tags of this range, in the order they were added.
+    pub fn provenance(&self) -> impl Iterator<Item = &str> {
+        self.provenance
+            .iter()
+            .flat_map(|tags| tags.iter().map(|tag| &**tag))
+    }
+
+    /// Helper function building a range with the provenance of this one.
+    fn with_segments(&self, segments: SmallVec<Interval<V>>) -> Self {
+        Self {
+            segments,
+            provenance: self.provenance.clone(),
+        }
+    }
+}
+
+/// Tags of two ranges, without duplicates.
+/// Shared tags are not copied when only one of the ranges has some.
+fn merge_provenance(p1: &Option<Provenance>, p2: &Option<Provenance>) -> Option<Provenance> {
+    match (p1, p2) {
+        (None, p) | (p, None) => p.clone(),
+        (Some(tags1), Some(tags2)) if Arc::ptr_eq(tags1, tags2) => p1.clone(),
+        (Some(tags1), Some(tags2)) => {
+            let mut tags = tags1.to_vec();
+            for tag in tags2.iter() {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            Some(tags.into())
+        }
+    }
+}
+
+// Countable versions.
+impl<V: Version> Range<V> {
+    /// Count the number of versions contained in the range,
+    /// for version types implementing [Version::approx_distance].
+    /// Returns [None] if the range is not bounded,
+    /// or if versions of that type cannot be counted.
+    pub fn count_versions(&self) -> Option<u64> {
+        self.segments
+            .iter()
+            .map(|(start, end)| count_interval(start, end))
+            .sum()
+    }
+}
+
+/// Number of versions in an interval, for version types implementing [Version::approx_distance].
+fn count_interval<V: Version>(start: &Bound<V>, end: &Bound<V>) -> Option<u64> {
+    let lowest;
+    let (start, excluded_start) = match start {
+        Included(s) => (s, 0),
+        Excluded(s) => (s, 1),
+        Unbounded => {
+            lowest = V::lowest();
+            (&lowest, 0)
+        }
+    };
+    let (end, included_end) = match end {
+        Included(e) => (e, 1),
+        Excluded(e) => (e, 0),
+        Unbounded => return None,
+    };
+    let distance = V::approx_distance(start, end)? + included_end;
+    Some(distance.saturating_sub(excluded_start))
+}
+
+// Number versions.
+impl Range<NumberVersion> {
+    /// Count the number of versions contained in the range.
+    /// Returns [None] if the range is not bounded.
+    pub fn volume(&self) -> Option<u64> {
+        self.count_versions()
+    }
+
+    /// Count the number of versions of the universe that are not in this range,
+    /// without computing the complement.
+    /// Returns [None] if the universe is not bounded.
+    pub fn complement_volume(&self, universe: &Self) -> Option<u64> {
+        let universe_volume = universe.volume()?;
+        let mut covered = 0;
+        universe.for_each_intersection_segment(self, |start, end| {
+            // The universe is bounded so the intersection is too.
+            covered += count_interval(start, end).unwrap_or(0);
+        });
+        Some(universe_volume - covered)
+    }
+}
+
+// Semantic versions.
+impl Range<SemanticVersion> {
+    /// Versions compatible with the given one, like the `^1.2.3` requirement:
+    /// versions with the same major number, or with the same leftmost
+    /// non-zero number for `0.x` versions.
+    /// So `^1.2.3` is `1.2.3 <= v < 2.0.0`, `^0.2.3` is `0.2.3 <= v < 0.3.0`
+    /// and `^0.0.3` is `0.0.3 <= v < 0.0.4`.
+    /// A number that cannot be incremented carries over to the number on its left,
+    /// and there is no upper bound past the highest major number.
+    pub fn caret(v: impl Into<SemanticVersion>) -> Self {
+        let v = v.into();
+        let upper = match v.into() {
+            (0, 0, patch) => next_patch(0, 0, patch),
+            (0, minor, _) => next_minor(0, minor),
+            (major, _, _) => next_major(major),
+        };
+        Self::between_or_higher(v, upper)
+    }
+
+    /// Versions with the same major and minor numbers as the given one,
+    /// and at least its patch number, like the `~1.2.3` requirement.
+    /// So `~1.2.3` is `1.2.3 <= v < 1.3.0`.
+    pub fn tilde(v: impl Into<SemanticVersion>) -> Self {
+        let v = v.into();
+        let (major, minor, _) = v.into();
+        Self::between_or_higher(v, next_minor(major, minor))
+    }
+
+    /// Helper function for the upper bounds of [caret](Self::caret) and [tilde](Self::tilde),
+    /// which do not exist past the highest major number.
+    fn between_or_higher(v: SemanticVersion, upper: Option<(u32, u32, u32)>) -> Self {
+        match upper {
+            Some(upper) => Self::between(v, upper),
+            None => Self::higher_than(v),
+        }
+    }
+}
+
+/// Lowest version with a higher major number, if there is one.
+fn next_major(major: u32) -> Option<(u32, u32, u32)> {
+    major.checked_add(1).map(|major| (major, 0, 0))
+}
+
+/// Lowest version with a higher minor number, or the next major one if the minor number is maxed.
+fn next_minor(major: u32, minor: u32) -> Option<(u32, u32, u32)> {
+    match minor.checked_add(1) {
+        Some(minor) => Some((major, minor, 0)),
+        None => next_major(major),
+    }
+}
+
+/// Lowest version with a higher patch number, or the next minor one if the patch number is maxed.
+fn next_patch(major: u32, minor: u32, patch: u32) -> Option<(u32, u32, u32)> {
+    match patch.checked_add(1) {
+        Some(patch) => Some((major, minor, patch)),
+        None => next_minor(major, minor),
+    }
+}
+
+// CONSTRAINTS #################################################################
+
+/// Comparison operator of a constraint on versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompOp {
+    /// Greater than or equal to the version.
+    Gte,
+    /// Strictly greater than the version.
+    Gt,
+    /// Lower than or equal to the version.
+    Lte,
+    /// Strictly lower than the version.
+    Lt,
+    /// Equal to the version.
+    Eq,
+    /// Different from the version.
+    Ne,
+}
+
+/// Error creating a [Range] from a list of constraints or of intervals.
+#[derive(Error, Debug, PartialEq)]
+pub enum RangeError {
+    /// No version satisfies all the constraints together.
+    #[error("constraints cannot be satisfied by any version")]
+    Unsatisfiable,
+    /// An interval does not have its lower bound strictly lower than its upper bound.
+    #[error("interval at index {index} is empty")]
+    EmptyInterval {
+        /// Position of the interval in the input.
+        index: usize,
+    },
+}
+
+impl<V: Version> Range<V> {
+    /// Range of versions satisfying all of the given constraints,
+    /// such as `[(Gte, 1), (Lt, 2)]`.
+    /// An empty list of constraints gives the full range.
+    /// Returns [RangeError::Unsatisfiable] if the range would be empty.
+    pub fn from_constraints(constraints: &[(CompOp, V)]) -> Result<Self, RangeError> {
+        let mut range = Self::any();
+        for (op, v) in constraints {
+            let v = v.clone();
+            let constraint = match op {
+                CompOp::Gte => Self::higher_than(v),
+                CompOp::Gt => Self::higher_than_exclusive(v),
+                CompOp::Lte => Self::at_most(v),
+                CompOp::Lt => Self::strictly_lower_than(v),
+                CompOp::Eq => Self::exact(v),
+                CompOp::Ne => Self::exact(v).negate(),
+            };
+            range.intersect_assign(&constraint);
+        }
+        if range.is_empty() {
+            Err(RangeError::Unsatisfiable)
+        } else {
+            Ok(range)
+        }
+    }
+}
+
+/// Build a range from intervals between a lower and an upper bound.
+/// Intervals may be given in any order and may overlap.
+/// Empty intervals are ignored.
+impl<V: Version> std::iter::FromIterator<(Bound<V>, Bound<V>)> for Range<V> {
+    fn from_iter<I: IntoIterator<Item = (Bound<V>, Bound<V>)>>(intervals: I) -> Self {
+        let mut intervals: Vec<_> = intervals
+            .into_iter()
+            .filter_map(normalize_interval)
+            .collect();
+        intervals.sort_by(|(start1, _), (start2, _)| cmp_start(start1, start2));
+        let mut segments = SmallVec::empty();
+        let mut current: Option<Interval<V>> = None;
+        for (start, end) in intervals {
+            match current.as_mut() {
+                None => current = Some((start, end)),
+                // The current segment is infinite so it contains all the next ones.
+                Some((_, Unbounded)) => break,
+                // The intervals overlap or are contiguous, they are merged.
+                Some((_, current_end)) if !is_separated(current_end, &start) => {
+                    if cmp_end(&end, current_end) == Ordering::Greater {
+                        *current_end = end;
+                    }
+                }
+                Some(_) => {
+                    segments.push(current.take().unwrap());
+                    current = Some((start, end));
+                }
+            }
+        }
+        if let Some(last) = current {
+            segments.push(last);
+        }
+        Self::from_segments(segments)
+    }
+}
+
+/// Build a range from intervals `start <= v < end`, where an end of [None] means no upper bound.
+/// Intervals may be given in any order and may overlap.
+/// Empty intervals, with `end <= start`, are ignored.
+impl<V: Version> std::iter::FromIterator<(V, Option<V>)> for Range<V> {
+    fn from_iter<I: IntoIterator<Item = (V, Option<V>)>>(intervals: I) -> Self {
+        intervals
+            .into_iter()
+            .map(|(start, end)| (Included(start), end.map_or(Unbounded, Excluded)))
+            .collect()
+    }
+}
+
+impl<V: Version> Range<V> {
+    /// Range whose segment bounds are the given versions, once sorted and deduplicated,
+    /// with a last unbounded segment if their number is odd.
+    /// Each bound is included or excluded depending on the boolean paired with its version.
+    /// Used to build arbitrary ranges for property-based testing and fuzzing.
+    #[cfg(any(test, feature = "proptest", feature = "arbitrary"))]
+    pub(crate) fn from_bounds_list(mut bounds: Vec<(V, bool)>) -> Self {
+        bounds.sort_unstable_by(|(v1, _), (v2, _)| v1.cmp(v2));
+        bounds.dedup_by(|(v1, _), (v2, _)| v1 == v2);
+        let bound = |(v, included): &(V, bool)| {
+            if *included {
+                Included(v.clone())
+            } else {
+                Excluded(v.clone())
+            }
+        };
+        // Normalized bounds of successive intervals may become contiguous, so they are collected.
+        let mut pair_iter = bounds.chunks_exact(2);
+        let mut intervals: Vec<_> = (&mut pair_iter)
+            .map(|pair| (bound(&pair[0]), bound(&pair[1])))
+            .collect();
+        if let [v] = pair_iter.remainder() {
+            intervals.push((bound(v), Unbounded));
+        }
+        intervals.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, V: Version + arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Range<V> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_bounds_list(u.arbitrary()?))
+    }
+}
+
+/// Same as the [FromIterator](std::iter::FromIterator) implementation,
+/// except that empty intervals are an error instead of being ignored.
+impl<V: Version> TryFrom<Vec<(V, Option<V>)>> for Range<V> {
+    type Error = RangeError;
+
+    fn try_from(intervals: Vec<(V, Option<V>)>) -> Result<Self, Self::Error> {
+        let empty = intervals
+            .iter()
+            .position(|(start, end)| end.as_ref().is_some_and(|end| end <= start));
+        match empty {
+            Some(index) => Err(RangeError::EmptyInterval { index }),
+            None => Ok(intervals.into_iter().collect()),
+        }
+    }
+}
+
+// CARGO REQUIREMENTS ##########################################################
+
+/// Error creating a [Range] from a Cargo version requirement.
+#[cfg(feature = "semver")]
+#[derive(Error, Debug, PartialEq)]
+pub enum CargoSpecError {
+    /// A comparator of the requirement is not valid.
+    #[error("invalid comparator '{comparator}' in '{full_spec}': {reason}")]
+    InvalidComparator {
+        /// Requirement that was being parsed.
+        full_spec: String,
+        /// Comparator of the requirement where parsing failed.
+        comparator: String,
+        /// Reason why that comparator is not valid.
+        reason: String,
+    },
+}
+
+/// Error converting a [Range] into a Cargo version requirement.
+#[cfg(feature = "semver")]
+#[derive(Error, Debug, PartialEq)]
+pub enum ToSemverError {
+    /// The range is empty or made of multiple segments,
+    /// which a requirement cannot express.
+    #[error("range cannot be expressed as a single semver requirement")]
+    NotRepresentable,
+}
+
+#[cfg(feature = "semver")]
+impl Range<semver::Version> {
+    /// Parse a Cargo version requirement, such as `">=1.0, <2.0"`,
+    /// `"^1.5.3"`, `"~0.5"` or `"*"`, into a range.
+    /// The `^`, `~`, `*` operators and bare versions follow Cargo semantics.
+    ///
+    /// Beware that Cargo only selects pre-release versions
+    /// if they are explicitly mentioned in the requirement.
+    /// This rule is not applied here, the range contains every
+    /// pre-release that is ordered between its bounds.
+    /// Upper bounds exclude the pre-releases of the bound though,
+    /// so `"^1.5.3"` is `1.5.3 <= v < 2.0.0-0`.
+    pub fn from_cargo_spec(s: &str) -> Result<Self, CargoSpecError> {
+        let mut range = Self::any();
+        for comparator in s.split(',').map(str::trim) {
+            if comparator == "*" {
+                continue;
+            }
+            let invalid = |reason: String| CargoSpecError::InvalidComparator {
+                full_spec: s.to_string(),
+                comparator: comparator.to_string(),
+                reason,
+            };
+            let parsed =
+                semver::Comparator::parse(comparator).map_err(|e| invalid(e.to_string()))?;
+            let comparator_range =
+                Self::from_cargo_comparator(&parsed).map_err(|e| invalid(e.to_string()))?;
+            range.intersect_assign(&comparator_range);
+        }
+        Ok(range)
+    }
+
+    /// Check if the range contains a version, following the Cargo and npm rule
+    /// that pre-releases are only matched when explicitly requested.
+    /// A pre-release version is contained only if a segment of the range
+    /// starts at a pre-release with the same major, minor and patch numbers,
+    /// such as `"1.2.3-alpha <= v"` for `>=1.2.3-alpha`,
+    /// which contains `1.2.3-beta` but not `1.2.4-alpha`.
+    /// Release versions are contained like with [contains](Self::contains).
+    pub fn contains_prerelease_aware(&self, version: &semver::Version) -> bool {
+        if !self.contains(version) {
+            return false;
+        }
+        if version.pre.is_empty() {
+            return true;
+        }
+        self.segments.iter().any(|(start, _)| match start {
+            Included(start) | Excluded(start) => {
+                !start.pre.is_empty()
+                    && (start.major, start.minor, start.patch)
+                        == (version.major, version.minor, version.patch)
+            }
+            Unbounded => false,
+        })
+    }
+
+    /// Range of versions matching an already parsed requirement,
+    /// following the same rules as [from_cargo_spec](Self::from_cargo_spec).
+    /// Fails like [from_cargo_spec](Self::from_cargo_spec) for comparators
+    /// with operators unknown at the time of writing,
+    /// or with bounds that would need a version number higher than `u64::MAX`.
+    pub fn from_requirement(req: &semver::VersionReq) -> Result<Self, CargoSpecError> {
+        let mut range = Self::any();
+        for comparator in &req.comparators {
+            let comparator_range = Self::from_cargo_comparator(comparator).map_err(|reason| {
+                CargoSpecError::InvalidComparator {
+                    full_spec: req.to_string(),
+                    comparator: comparator.to_string(),
+                    reason: reason.to_string(),
+                }
+            })?;
+            range.intersect_assign(&comparator_range);
+        }
+        Ok(range)
+    }
+
+    /// Range of versions matching a single comparator of a Cargo requirement.
+    /// Fails for operators unknown at the time of writing,
+    /// and when a bound would need a version number higher than `u64::MAX`.
+    fn from_cargo_comparator(c: &semver::Comparator) -> Result<Self, &'static str> {
+        use semver::Op;
+        let major = c.major;
+        let next = |n: u64| n.checked_add(1).ok_or("version number is too large");
+        let release = semver::Version::new;
+        // First pre-release of a version, lower than any other pre-release of it.
+        let first_pre = |major, minor, patch| {
+            let mut v = semver::Version::new(major, minor, patch);
+            v.pre = semver::Prerelease::new("0").unwrap();
+            v
+        };
+        // Complete version of the comparator, when minor and patch are provided.
+        let full = |minor, patch| {
+            let mut v = semver::Version::new(major, minor, patch);
+            v.pre = c.pre.clone();
+            v
+        };
+        let range = match (c.op, c.minor, c.patch) {
+            (Op::Exact, Some(minor), Some(patch)) | (Op::Wildcard, Some(minor), Some(patch)) => {
+                Self::exact(full(minor, patch))
+            }
+            (Op::Exact, Some(minor), None) | (Op::Wildcard, Some(minor), None) => {
+                Self::between(release(major, minor, 0), first_pre(major, next(minor)?, 0))
+            }
+            (Op::Exact, None, _) | (Op::Wildcard, None, _) => {
+                Self::between(release(major, 0, 0), first_pre(next(major)?, 0, 0))
+            }
+            (Op::Greater, Some(minor), Some(patch)) => {
+                Self::higher_than_exclusive(full(minor, patch))
+            }
+            (Op::Greater, Some(minor), None) => Self::higher_than(release(major, next(minor)?, 0)),
+            (Op::Greater, None, _) => Self::higher_than(release(next(major)?, 0, 0)),
+            (Op::GreaterEq, Some(minor), Some(patch)) => Self::higher_than(full(minor, patch)),
+            (Op::GreaterEq, Some(minor), None) => Self::higher_than(release(major, minor, 0)),
+            (Op::GreaterEq, None, _) => Self::higher_than(release(major, 0, 0)),
+            (Op::Less, Some(minor), Some(patch)) => Self::strictly_lower_than(full(minor, patch)),
+            (Op::Less, Some(minor), None) => Self::strictly_lower_than(first_pre(major, minor, 0)),
+            (Op::Less, None, _) => Self::strictly_lower_than(first_pre(major, 0, 0)),
+            (Op::LessEq, Some(minor), Some(patch)) => Self::at_most(full(minor, patch)),
+            (Op::LessEq, Some(minor), None) => {
+                Self::strictly_lower_than(first_pre(major, next(minor)?, 0))
+            }
+            (Op::LessEq, None, _) => Self::strictly_lower_than(first_pre(next(major)?, 0, 0)),
+            (Op::Tilde, Some(minor), Some(patch)) => {
+                Self::between(full(minor, patch), first_pre(major, next(minor)?, 0))
+            }
+            (Op::Tilde, Some(minor), None) => {
+                Self::between(release(major, minor, 0), first_pre(major, next(minor)?, 0))
+            }
+            (Op::Tilde, None, _) => {
+                Self::between(release(major, 0, 0), first_pre(next(major)?, 0, 0))
+            }
+            (Op::Caret, Some(minor), Some(patch)) => {
+                let upper = if major > 0 {
+                    first_pre(next(major)?, 0, 0)
+                } else if minor > 0 {
+                    first_pre(0, next(minor)?, 0)
+                } else {
+                    first_pre(0, 0, next(patch)?)
+                };
+                Self::between(full(minor, patch), upper)
+            }
+            (Op::Caret, Some(minor), None) => {
+                let upper = if major > 0 {
+                    first_pre(next(major)?, 0, 0)
                 } else {
-                    Self::strictly_lower_than(v.clone())
+                    first_pre(0, next(minor)?, 0)
+                };
+                Self::between(release(major, minor, 0), upper)
+            }
+            (Op::Caret, None, _) => {
+                Self::between(release(major, 0, 0), first_pre(next(major)?, 0, 0))
+            }
+            _ => return Err("unsupported operator"),
+        };
+        Ok(range)
+    }
+
+    /// Write the range as a requirement that can be parsed by
+    /// [semver::VersionReq::parse], such as `">=1.0.0, <2.0.0"`.
+    ///
+    /// Only ranges made of a single segment can be expressed this way,
+    /// other ranges, including the empty one,
+    /// return [ToSemverError::NotRepresentable].
+    /// Beware that the requirement follows the semver rules for pre-releases
+    /// when matching versions, which only match pre-releases explicitly mentioned.
+    /// It is thus converted back to the same range by [from_cargo_spec](Self::from_cargo_spec),
+    /// but [semver::VersionReq::matches] may reject pre-releases contained in the range.
+    pub fn to_semver_req_string(&self) -> Result<String, ToSemverError> {
+        let (start, end) = match self.segments.as_slice() {
+            [(Included(start), Included(end))] if start == end => return Ok(format!("={}", start)),
+            [(Unbounded, Included(end))] if end == &semver::Version::lowest() => {
+                return Ok(format!("={}", end))
+            }
+            [(start, end)] => (start, end),
+            _ => return Err(ToSemverError::NotRepresentable),
+        };
+        let start = match start {
+            Included(start) => Some(format!(">={}", start)),
+            Excluded(start) => Some(format!(">{}", start)),
+            Unbounded => None,
+        };
+        let end = match end {
+            Included(end) => Some(format!("<={}", end)),
+            Excluded(end) => Some(format!("<{}", end)),
+            Unbounded => None,
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => Ok(format!("{}, {}", start, end)),
+            (Some(bound), None) | (None, Some(bound)) => Ok(bound),
+            (None, None) => Ok("*".to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "semver-syntax")]
+impl Range<SemanticVersion> {
+    /// Parse a Cargo or npm style version requirement, such as `"^1.2.3"`,
+    /// `"~1.2"`, `">=1.0, <2.0"` or `"1.*"`, into a range of semantic versions.
+    /// It is parsed by `Range::<semver::Version>::from_cargo_spec`,
+    /// whose bounds are then converted to semantic versions.
+    ///
+    /// [SemanticVersion] has no pre-releases,
+    /// so requirements mentioning one are rejected,
+    /// as well as lower bounds with version numbers that do not fit in a `u32`.
+    /// Upper bounds higher than any [SemanticVersion] are dropped.
+    pub fn parse_requirement(s: &str) -> Result<Self, CargoSpecError> {
+        // Comparators are converted one at a time to report the one that is not valid.
+        // Converting bounds preserves their order, so intersecting the converted comparators
+        // gives the same range as converting their intersection.
+        let mut range = Self::any();
+        for comparator in s.split(',').map(str::trim) {
+            let invalid = |reason: &str| CargoSpecError::InvalidComparator {
+                full_spec: s.to_string(),
+                comparator: comparator.to_string(),
+                reason: reason.to_string(),
+            };
+            let comparator_range =
+                Range::<semver::Version>::from_cargo_spec(comparator).map_err(|e| match e {
+                    CargoSpecError::InvalidComparator { reason, .. } => invalid(&reason),
+                })?;
+            // The only pre-releases in the bounds of a Cargo requirement that does not
+            // mention any are the "0" ones, lower than any other pre-release,
+            // excluding the pre-releases of an upper bound.
+            let release = |v: &semver::Version| {
+                if !v.pre.is_empty() && v.pre.as_str() != "0" {
+                    return Err(invalid("pre-releases are not supported"));
+                }
+                let number = |n| u32::try_from(n).ok();
+                Ok(number(v.major)
+                    .zip(number(v.minor))
+                    .zip(number(v.patch))
+                    .map(|((major, minor), patch)| SemanticVersion::new(major, minor, patch)))
+            };
+            // Pre-releases are lower than their release, so a bound on a pre-release
+            // includes the release in a lower bound, and excludes it in an upper bound.
+            let start = |bound: &Bound<semver::Version>| {
+                let too_large = || invalid("version number is too large");
+                Ok(match bound {
+                    Excluded(v) if v.pre.is_empty() => Excluded(release(v)?.ok_or_else(too_large)?),
+                    Included(v) | Excluded(v) => Included(release(v)?.ok_or_else(too_large)?),
+                    Unbounded => Unbounded,
+                })
+            };
+            let end = |bound: &Bound<semver::Version>| {
+                Ok(match bound {
+                    Included(v) if v.pre.is_empty() => release(v)?.map_or(Unbounded, Included),
+                    Included(v) | Excluded(v) => release(v)?.map_or(Unbounded, Excluded),
+                    Unbounded => Unbounded,
+                })
+            };
+            let converted: Self = comparator_range
+                .segments
+                .iter()
+                .map(|(s, e)| Ok((start(s)?, end(e)?)))
+                .collect::<Result<_, CargoSpecError>>()?;
+            range.intersect_assign(&converted);
+        }
+        Ok(range)
+    }
+}
+
+// REPORT ######################################################################
+
+/// Way of writing ranges, used when displaying them with
+/// [display_with](Range::display_with) and when generating reports with
+/// [report_with_formatter](crate::report::DefaultStringReporter::report_with_formatter).
+pub trait RangeFormatter<V: Version> {
+    /// Write the range.
+    fn fmt_range(&self, range: &Range<V>, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Formatter used by the [Display](fmt::Display) implementation of [Range],
+/// writing ranges like `"1 <= v < 3"`, `"1 < v <= 3"`, or `"[ 1, 3 [  ] 5, ∞ ["`
+/// when they are made of multiple segments.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRangeFormatter;
+
+impl<V: Version> RangeFormatter<V> for DefaultRangeFormatter {
+    fn fmt_range(&self, range: &Range<V>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(version) = range.exact_version() {
+            return write!(f, "{}", version);
+        }
+        match range.segments.as_slice() {
+            [] => write!(f, "∅"),
+            [(Unbounded, Unbounded)] => write!(f, "∗"),
+            [(start, end)] => {
+                match start {
+                    Included(start) => write!(f, "{} <= ", start)?,
+                    Excluded(start) => write!(f, "{} < ", start)?,
+                    Unbounded => {}
+                }
+                write!(f, "v")?;
+                match end {
+                    Included(end) => write!(f, " <= {}", end),
+                    Excluded(end) => write!(f, " < {}", end),
+                    Unbounded => Ok(()),
                 }
             }
-
-            // First high bound is not +∞
-            Some((v1, Some(v2))) => {
-                if v1 == &V::lowest() {
-                    Self::negate_segments(v2.clone(), &self.segments[1..])
-                } else {
-                    Self::negate_segments(V::lowest(), &self.segments)
-                }
+            more_than_one_interval => {
+                let string_intervals: Vec<_> = more_than_one_interval
+                    .iter()
+                    .map(interval_to_string)
+                    .collect();
+                write!(f, "{}", string_intervals.join("  "))
             }
         }
     }
+}
 
-    /// Helper function performing the negation of intervals in segments.
-    /// For example:
-    ///    [ (v1, None) ] => [ (start, Some(v1)) ]
-    ///    [ (v1, Some(v2)) ] => [ (start, Some(v1)), (v2, None) ]
-    fn negate_segments(start: V, segments: &[Interval<V>]) -> Range<V> {
-        let mut complement_segments = SmallVec::empty();
-        let mut start = Some(start);
-        for (v1, maybe_v2) in segments {
-            // start.unwrap() is fine because `segments` is not exposed,
-            // and our usage guaranties that only the last segment may contain a None.
-            complement_segments.push((start.unwrap(), Some(v1.to_owned())));
-            start = maybe_v2.to_owned();
-        }
-        if let Some(last) = start {
-            complement_segments.push((last, None));
-        }
+/// Range written with a given formatter.
+/// Created with [display_with](Range::display_with).
+pub struct RangeDisplay<'a, V: Version> {
+    range: &'a Range<V>,
+    formatter: &'a dyn RangeFormatter<V>,
+}
 
-        Self {
-            segments: complement_segments,
+impl<V: Version> Range<V> {
+    /// Display this range with a custom formatter,
+    /// for example to write `">=1.2.0, <2.0.0"` instead of `"1.2.0 <= v < 2.0.0"`.
+    pub fn display_with<'a>(&'a self, formatter: &'a dyn RangeFormatter<V>) -> RangeDisplay<'a, V> {
+        RangeDisplay {
+            range: self,
+            formatter,
         }
     }
+}
 
-    // Union and intersection ##################################################
+impl<'a, V: Version> fmt::Display for RangeDisplay<'a, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.formatter.fmt_range(self.range, f)
+    }
+}
 
-    /// Compute the union of two sets of versions.
-    pub fn union(&self, other: &Self) -> Self {
-        self.negate().intersection(&other.negate()).negate()
+impl<V: Version> fmt::Display for Range<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DefaultRangeFormatter.fmt_range(self, f)
     }
+}
 
-    /// Compute the intersection of two sets of versions.
-    pub fn intersection(&self, other: &Self) -> Self {
-        let mut segments = SmallVec::empty();
-        let mut left_iter = self.segments.iter();
-        let mut right_iter = other.segments.iter();
-        let mut left = left_iter.next();
-        let mut right = right_iter.next();
-        loop {
-            match (left, right) {
-                // Both left and right still contain a finite interval:
-                (Some((l1, Some(l2))), Some((r1, Some(r2)))) => {
-                    if l2 <= r1 {
-                        // Intervals are disjoint, progress on the left.
-                        left = left_iter.next();
-                    } else if r2 <= l1 {
-                        // Intervals are disjoint, progress on the right.
-                        right = right_iter.next();
-                    } else {
-                        // Intervals are not disjoint.
-                        let start = l1.max(r1).to_owned();
-                        if l2 < r2 {
-                            segments.push((start, Some(l2.to_owned())));
-                            left = left_iter.next();
-                        } else {
-                            segments.push((start, Some(r2.to_owned())));
-                            right = right_iter.next();
-                        }
-                    }
-                }
+/// Write an interval with brackets, turned outwards for excluded bounds,
+/// like `"[ 1, 3 ["` for `1 <= v < 3`.
+/// A missing lower bound is written as the lowest version.
+fn interval_to_string<V: Version>((start, end): &Interval<V>) -> String {
+    let start = match start {
+        Included(start) => format!("[ {}", start),
+        Excluded(start) => format!("] {}", start),
+        Unbounded => format!("[ {}", V::lowest()),
+    };
+    let end = match end {
+        Included(end) => format!("{} ]", end),
+        Excluded(end) => format!("{} [", end),
+        Unbounded => "∞ [".to_string(),
+    };
+    format!("{}, {}", start, end)
+}
 
-                // Right contains an infinite interval:
-                (Some((l1, Some(l2))), Some((r1, None))) => match l2.cmp(r1) {
-                    Ordering::Less => {
-                        left = left_iter.next();
-                    }
-                    Ordering::Equal => {
-                        for l in left_iter.cloned() {
-                            segments.push(l)
-                        }
-                        break;
-                    }
-                    Ordering::Greater => {
-                        let start = l1.max(r1).to_owned();
-                        segments.push((start, Some(l2.to_owned())));
-                        for l in left_iter.cloned() {
-                            segments.push(l)
-                        }
-                        break;
-                    }
-                },
+// PARSING #####################################################################
 
-                // Left contains an infinite interval:
-                (Some((l1, None)), Some((r1, Some(r2)))) => match r2.cmp(l1) {
-                    Ordering::Less => {
-                        right = right_iter.next();
-                    }
-                    Ordering::Equal => {
-                        for r in right_iter.cloned() {
-                            segments.push(r)
-                        }
-                        break;
-                    }
-                    Ordering::Greater => {
-                        let start = l1.max(r1).to_owned();
-                        segments.push((start, Some(r2.to_owned())));
-                        for r in right_iter.cloned() {
-                            segments.push(r)
-                        }
-                        break;
-                    }
-                },
+/// Error parsing a [Range] written in the format of its [Display](fmt::Display) implementation.
+#[derive(Error, Debug, PartialEq)]
+pub enum RangeParseError {
+    /// The text does not follow the format of a range.
+    #[error("invalid range '{range}'")]
+    InvalidFormat {
+        /// Text that was being parsed.
+        range: String,
+    },
+    /// A version of the range could not be parsed.
+    #[error("invalid version '{version}' in range '{range}': {reason}")]
+    InvalidVersion {
+        /// Text that was being parsed.
+        range: String,
+        /// Version that could not be parsed.
+        version: String,
+        /// Reason why that version is not valid.
+        reason: String,
+    },
+}
 
-                // Both sides contain an infinite interval:
-                (Some((l1, None)), Some((r1, None))) => {
-                    let start = l1.max(r1).to_owned();
-                    segments.push((start, None));
-                    break;
-                }
+/// Parse a range written like its [Display](fmt::Display) implementation does,
+/// such as `"∅"`, `"∗"`, `"1 <= v < 3"`, `"v <= 3"` or `"[ 1, 3 [  ] 5, ∞ ["`.
+impl<V: Version + FromStr> FromStr for Range<V>
+where
+    V::Err: fmt::Display,
+{
+    type Err = RangeParseError;
 
-                // Left or right has ended.
-                _ => {
-                    break;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RangeParseError::InvalidFormat {
+            range: s.to_string(),
+        };
+        let version = |v: &str| {
+            v.trim()
+                .parse::<V>()
+                .map_err(|e| RangeParseError::InvalidVersion {
+                    range: s.to_string(),
+                    version: v.trim().to_string(),
+                    reason: e.to_string(),
+                })
+        };
+        let s_trimmed = s.trim();
+        if s_trimmed == "∅" {
+            return Ok(Self::none());
+        }
+        if s_trimmed == "∗" {
+            return Ok(Self::any());
+        }
+        if s_trimmed.starts_with(['[', ']']) {
+            let mut intervals = Vec::new();
+            let mut rest = s_trimmed;
+            while let Some(opening) = rest.chars().next().filter(|c| *c == '[' || *c == ']') {
+                let (start, interval) = rest[1..].split_once(',').ok_or_else(invalid)?;
+                let closing_at = interval.find(['[', ']']).ok_or_else(invalid)?;
+                let (end, remaining) = interval.split_at(closing_at);
+                let start = match opening {
+                    '[' => Included(version(start)?),
+                    _ => Excluded(version(start)?),
+                };
+                let end = match (end.trim(), remaining.starts_with('[')) {
+                    ("∞", true) => Unbounded,
+                    ("∞", false) => return Err(invalid()),
+                    (end, true) => Excluded(version(end)?),
+                    (end, false) => Included(version(end)?),
+                };
+                if !is_valid_interval(&start, &end) {
+                    return Err(invalid());
                 }
+                intervals.push((start, end));
+                rest = remaining[1..].trim_start();
             }
+            if !rest.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(intervals.into_iter().collect());
         }
-
-        Self { segments }
-    }
-}
-
-// Other useful functions.
-impl<V: Version> Range<V> {
-    /// Check if a range contains a given version.
-    pub fn contains(&self, version: &V) -> bool {
-        for (v1, maybe_v2) in &self.segments {
-            match maybe_v2 {
-                None => return v1 <= version,
-                Some(v2) => {
-                    if version < v1 {
-                        return false;
-                    } else if version < v2 {
-                        return true;
-                    }
-                }
+        let lower = |lower: &str| match lower.strip_suffix("<=") {
+            Some(v) => version(v).map(Included),
+            None => match lower.strip_suffix('<') {
+                Some(v) => version(v).map(Excluded),
+                None => Err(invalid()),
+            },
+        };
+        let upper = |upper: &str| match upper.strip_prefix("<=") {
+            Some(v) => version(v).map(Included),
+            None => match upper.strip_prefix('<') {
+                Some(v) => version(v).map(Excluded),
+                None => Err(invalid()),
+            },
+        };
+        if let Some(end) = s_trimmed.strip_prefix("v ") {
+            Ok(Self::from_bounds(Unbounded, upper(end.trim())?))
+        } else if let Some(start) = s_trimmed.strip_suffix(" v") {
+            Ok(Self::from_bounds(lower(start.trim())?, Unbounded))
+        } else if let Some((start, end)) = s_trimmed.split_once(" v ") {
+            let (start, end) = (lower(start.trim())?, upper(end.trim())?);
+            if !is_valid_interval(&start, &end) {
+                return Err(invalid());
             }
+            Ok(Self::from_bounds(start, end))
+        } else if s_trimmed.contains(|c: char| c.is_whitespace() || c == '<') {
+            Err(invalid())
+        } else {
+            Ok(Self::exact(version(s_trimmed)?))
         }
-        false
-    }
-
-    /// Return the lowest version in the range (if there is one).
-    pub fn lowest_version(&self) -> Option<V> {
-        self.segments.first().map(|(start, _)| start).cloned()
     }
 }
 
-// REPORT ######################################################################
+/// Serialize ranges as human-readable strings written like their
+/// [Display](fmt::Display) implementation does, such as `"1.0.0 <= v < 2.0.0"`,
+/// and deserialize them with their [FromStr] implementation.
+/// Unlike the default list of intervals, this is easy to read and write by hand,
+/// and can be used on a field with
+/// `#[serde(with = "pubgrub::range::serde_string")]`.
+#[cfg(feature = "serde")]
+pub mod serde_string {
+    use std::fmt;
+    use std::str::FromStr;
 
-impl<V: Version> fmt::Display for Range<V> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.segments.as_slice() {
-            [] => write!(f, "∅"),
-            [(start, None)] if start == &V::lowest() => write!(f, "∗"),
-            [(start, None)] => write!(f, "{} <= v", start),
-            [(start, Some(end))] if end == &start.bump() => write!(f, "{}", start),
-            [(start, Some(end))] if start == &V::lowest() => write!(f, "v < {}", end),
-            [(start, Some(end))] => write!(f, "{} <= v < {}", start, end),
-            more_than_one_interval => {
-                let string_intervals: Vec<_> = more_than_one_interval
-                    .iter()
-                    .map(interval_to_string)
-                    .collect();
-                write!(f, "{}", string_intervals.join("  "))
-            }
-        }
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Range;
+    use crate::version::Version;
+
+    /// Serialize a range as a string.
+    pub fn serialize<V: Version, S: Serializer>(
+        range: &Range<V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(range)
     }
-}
 
-fn interval_to_string<V: Version>((start, maybe_end): &Interval<V>) -> String {
-    match maybe_end {
-        Some(end) => format!("[ {}, {} [", start, end),
-        None => format!("[ {}, ∞ [", start),
+    /// Deserialize a range from a string.
+    pub fn deserialize<'de, V, D>(deserializer: D) -> Result<Range<V>, D::Error>
+    where
+        V: Version + FromStr,
+        V::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
     }
 }
 
 // TESTS #######################################################################
 
 #[cfg(test)]
-pub mod tests {
+pub(crate) mod tests {
     use proptest::prelude::*;
 
-    use crate::version::NumberVersion;
+    use crate::version::{NumberVersion, SemanticVersion};
 
     use super::*;
 
     pub fn strategy() -> impl Strategy<Value = Range<NumberVersion>> {
-        prop::collection::vec(any::<u32>(), 0..10).prop_map(|mut vec| {
-            vec.sort_unstable();
-            vec.dedup();
-            let mut pair_iter = vec.chunks_exact(2);
-            let mut segments = SmallVec::empty();
-            while let Some([v1, v2]) = pair_iter.next() {
-                segments.push((NumberVersion(*v1), Some(NumberVersion(*v2))));
-            }
-            if let [v] = pair_iter.remainder() {
-                segments.push((NumberVersion(*v), None));
-            }
-            Range { segments }
-        })
+        crate::proptest_support::arbitrary_range()
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Named {
+        #[serde(with = "crate::range::serde_string")]
+        range: Range<NumberVersion>,
     }
 
     fn version_strat() -> impl Strategy<Value = NumberVersion> {
         any::<u32>().prop_map(NumberVersion)
     }
 
+    fn bound_strat() -> impl Strategy<Value = Bound<NumberVersion>> {
+        prop_oneof![
+            version_strat().prop_map(Bound::Included),
+            version_strat().prop_map(Bound::Excluded),
+            Just(Bound::Unbounded),
+        ]
+    }
+
+    #[cfg(feature = "semver")]
+    fn cargo_comparator_strat() -> impl Strategy<Value = String> {
+        let op = prop::sample::select(vec!["", "=", ">", ">=", "<", "<=", "~", "^"]);
+        (
+            op,
+            0..3u64,
+            prop::option::of(0..3u64),
+            prop::option::of(0..3u64),
+        )
+            .prop_map(|(op, major, minor, patch)| match (minor, patch) {
+                (Some(minor), Some(patch)) => format!("{}{}.{}.{}", op, major, minor, patch),
+                (Some(minor), None) => format!("{}{}.{}", op, major, minor),
+                (None, _) => format!("{}{}", op, major),
+            })
+    }
+
+    #[cfg(feature = "semver")]
+    fn semver_strat() -> impl Strategy<Value = semver::Version> {
+        (0..4u64, 0..4u64, 0..4u64)
+            .prop_map(|(major, minor, patch)| semver::Version::new(major, minor, patch))
+    }
+
     proptest! {
 
         // Testing negate ----------------------------------
@@ -377,6 +2110,60 @@ pub mod tests {
             assert_eq!(r1.intersection(&r2).contains(&version), r1.contains(&version) && r2.contains(&version));
         }
 
+        #[test]
+        fn intersection_count_is_segments_len(r1 in strategy(), r2 in strategy()) {
+            assert_eq!(r1.intersection_count(&r2), r1.intersection(&r2).segments.len());
+        }
+
+        #[test]
+        fn lazy_intersection_is_intersection(r1 in strategy(), r2 in strategy(), r3 in strategy()) {
+            let lazy = r1.lazy_intersection(&r2);
+            let intersection = r1.intersection(&r2);
+            assert_eq!(lazy.is_empty(), intersection == Range::none());
+            assert!(lazy == intersection);
+            assert_eq!(lazy == r3, intersection == r3);
+            assert_eq!(lazy == r2, intersection == r2);
+        }
+
+        #[test]
+        fn monotone_mapping_preserves_contains(range in strategy(), version in version_strat()) {
+            let to_semantic = |v: NumberVersion| SemanticVersion::new(v.0, 0, 0);
+            let mapped = range.apply_version_mapping(Monotone(to_semantic));
+            assert_eq!(range.contains(&version), mapped.contains(&to_semantic(version)));
+        }
+
+        #[test]
+        fn shifted_mapping_of_exact_is_exact(version in 0..1000u32, shift in 1..100u32) {
+            let shift_by = |v: NumberVersion| NumberVersion(v.0 + shift);
+            let mapped = Range::exact(NumberVersion(version)).apply_version_mapping(Monotone(shift_by));
+            assert_eq!(mapped, Range::exact(NumberVersion(version + shift)));
+        }
+
+        #[test]
+        fn bounded_ranges(range in strategy(), version in version_strat()) {
+            if range.is_lower_bounded() {
+                assert!(!range.contains(&NumberVersion(0)));
+            } else if !range.is_empty() {
+                assert!(range.contains(&NumberVersion(0)));
+            }
+            if range.is_upper_bounded() {
+                assert!(range.volume().is_some());
+                assert!(range.intersection(&Range::higher_than(version)) != Range::higher_than(version));
+            }
+        }
+
+        #[test]
+        fn complement_volume_is_volume_of_difference(r1 in strategy(), r2 in strategy()) {
+            let expected = r2.volume().and(r2.intersection(&r1.negate()).volume());
+            assert_eq!(r1.complement_volume(&r2), expected);
+        }
+
+        #[test]
+        fn complement_segments_iter_is_negate(range in strategy()) {
+            let complement: Vec<_> = range.complement_segments_iter().collect();
+            assert_eq!(complement.as_slice(), range.negate().segments.as_slice());
+        }
+
         // Testing union -----------------------------------
 
         #[test]
@@ -384,11 +2171,246 @@ pub mod tests {
             assert_eq!(range.negate().union(&range), Range::any());
         }
 
+        #[test]
+        fn simplify_keeps_available_versions(range in strategy(), mut available in prop::collection::vec(version_strat(), 0..20)) {
+            available.sort_unstable();
+            available.dedup();
+            let simplified = range.simplify(&available);
+            for version in &available {
+                assert_eq!(simplified.contains(version), range.contains(version));
+            }
+            assert_eq!(simplified.simplify(&available), simplified);
+        }
+
+        #[test]
+        fn complement_within_universe(range in strategy(), universe in strategy()) {
+            let complement = range.complement_within(&universe);
+            assert_eq!(complement, universe.intersection(&range.negate()));
+            assert!(complement.is_subset_of(&universe));
+            assert_eq!(complement.union(&range.intersection(&universe)), universe);
+            assert_eq!(range.complement_within(&Range::any()), range.negate());
+        }
+
+        #[test]
+        fn bounding_interval_contains_range(range in strategy()) {
+            match range.bounding_interval() {
+                None => assert_eq!(range, Range::none()),
+                Some((start, end)) => assert!(range.is_subset_of(&Range::from_bounds(start, end))),
+            }
+        }
+
+        #[test]
+        fn from_versions_is_union_of_exact(versions in prop::collection::vec(0..20u32, 0..20)) {
+            let union = versions.iter().fold(Range::none(), |range, &v| range.union(&Range::exact(v)));
+            assert_eq!(Range::from_versions(versions.into_iter().map(NumberVersion)), union);
+        }
+
+        #[test]
+        fn contains_many_is_contains(range in strategy(), mut versions in prop::collection::vec(version_strat(), 0..20)) {
+            let expected: Vec<_> = versions.iter().map(|v| range.contains(v)).collect();
+            assert_eq!(range.contains_many(versions.iter()).collect::<Vec<_>>(), expected);
+            versions.sort_unstable();
+            let expected: Vec<_> = versions.iter().map(|v| range.contains(v)).collect();
+            assert_eq!(range.contains_many(versions.iter()).collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn assign_operations(r1 in strategy(), r2 in strategy(), v1 in version_strat(), v2 in version_strat()) {
+            for other in [r2.clone(), Range::between(v1, v2), Range::higher_than(v1), Range::strictly_lower_than(v2)] {
+                let mut intersection = r1.clone();
+                intersection.intersect_assign(&other);
+                assert_eq!(intersection, r1.intersection(&other));
+                let mut union = r1.clone();
+                union.union_assign(&other);
+                assert_eq!(union, r1.union(&other));
+            }
+        }
+
+        #[test]
+        fn is_empty_and_is_full(range in strategy()) {
+            assert_eq!(range.is_empty(), range == Range::none());
+            assert_eq!(range.is_full(), range == Range::any());
+            assert_eq!(range.negate().is_full(), range.is_empty());
+        }
+
+        #[test]
+        fn equal_ranges_have_equal_hashes(r1 in strategy(), r2 in strategy()) {
+            use std::hash::BuildHasher;
+            let hasher = std::hash::BuildHasherDefault::<rustc_hash::FxHasher>::default();
+            let hash = |range: &Range<NumberVersion>| hasher.hash_one(range);
+            let union = r1.union(&r2);
+            assert_eq!(hash(&union), hash(&r2.union(&r1)));
+            assert_eq!(hash(&r1), hash(&r1.intersection(&Range::any())));
+        }
+
+        #[test]
+        fn union_all_is_folded_union(ranges in prop::collection::vec(strategy(), 0..6)) {
+            let union = ranges.iter().fold(Range::none(), |acc, r| acc.union(r));
+            assert_eq!(Range::union_all(&ranges), union);
+        }
+
+        #[test]
+        fn intersection_all_is_folded_intersection(ranges in prop::collection::vec(strategy(), 0..6)) {
+            let intersection = ranges.iter().fold(Range::any(), |acc, r| acc.intersection(r));
+            assert_eq!(Range::intersection_all(&ranges), intersection);
+        }
+
+        #[test]
+        fn overlaps_is_non_empty_intersection(r1 in strategy(), r2 in strategy()) {
+            assert_eq!(r1.overlaps(&r2), r1.intersection(&r2) != Range::none());
+            assert_eq!(r1.is_disjoint(&r2), r1.intersection(&r2) == Range::none());
+        }
+
+        #[test]
+        fn is_subset_of_is_intersection(r1 in strategy(), r2 in strategy()) {
+            assert_eq!(r1.is_subset_of(&r2), r1.intersection(&r2) == r1);
+            assert!(r1.intersection(&r2).is_subset_of(&r1));
+            assert!(r1.is_subset_of(&r1.union(&r2)));
+        }
+
+        #[test]
+        fn segments_iterators(range in strategy()) {
+            let borrowed: Vec<_> = range.iter_segments().map(|(start, end)| (*start, *end)).collect();
+            assert_eq!(range.iter_segments().len(), range.segments.len());
+            assert_eq!(range.clone().into_segments().len(), range.segments.len());
+            let owned: Vec<_> = range.clone().into_segments().collect();
+            assert_eq!(borrowed.as_slice(), range.segments.as_slice());
+            assert_eq!(owned, borrowed);
+        }
+
+        #[test]
+        fn symmetric_difference_is_union_of_differences(r1 in strategy(), r2 in strategy(), version in version_strat()) {
+            let symmetric_difference = r1.symmetric_difference(&r2);
+            assert_eq!(symmetric_difference, r1.difference(&r2).union(&r2.difference(&r1)));
+            assert_eq!(symmetric_difference, r2.symmetric_difference(&r1));
+            assert_eq!(symmetric_difference.contains(&version), r1.contains(&version) != r2.contains(&version));
+        }
+
+        #[test]
+        fn difference_is_intersection_with_negation(r1 in strategy(), r2 in strategy()) {
+            let difference = r1.difference(&r2);
+            assert_eq!(difference, r1.intersection(&r2.negate()));
+            assert_eq!(difference.union(&r1.intersection(&r2)), r1);
+            assert!(!difference.overlaps(&r2));
+        }
+
+        #[test]
+        fn from_iter_is_union(intervals in prop::collection::vec((version_strat(), prop::option::of(version_strat())), 0..10)) {
+            let range: Range<NumberVersion> = intervals.iter().cloned().collect();
+            let expected = intervals.iter().fold(Range::none(), |acc, (start, end)| {
+                let interval = match end {
+                    None => Range::higher_than(*start),
+                    Some(end) => Range::between(*start, *end),
+                };
+                acc.union(&interval)
+            });
+            assert_eq!(range, expected);
+        }
+
+        #[test]
+        fn display_round_trip(range in strategy()) {
+            assert_eq!(range.to_string().parse::<Range<NumberVersion>>().unwrap(), range);
+        }
+
+        #[test]
+        fn union_is_de_morgan(r1 in strategy(), r2 in strategy()) {
+            assert_eq!(r1.union(&r2), r1.negate().intersection(&r2.negate()).negate());
+        }
+
         #[test]
         fn union_contains_either(r1 in strategy(), r2 in strategy(), version in version_strat()) {
             assert_eq!(r1.union(&r2).contains(&version), r1.contains(&version) || r2.contains(&version));
         }
 
+        // Testing exclusive bounds ------------------------
+
+        #[test]
+        fn higher_than_exclusive_contains(v1 in version_strat(), v2 in version_strat()) {
+            assert_eq!(Range::higher_than_exclusive(v1).contains(&v2), v1 < v2);
+        }
+
+        #[test]
+        fn at_most_contains(v1 in version_strat(), v2 in version_strat()) {
+            assert_eq!(Range::at_most(v1).contains(&v2), v2 <= v1);
+        }
+
+        #[test]
+        fn between_exclusive_contains(v1 in version_strat(), v2 in version_strat(), v in version_strat()) {
+            assert_eq!(Range::between_exclusive(v1, v2).contains(&v), v1 < v && v < v2);
+        }
+
+        #[test]
+        fn exclusive_aliases(v1 in version_strat(), v2 in version_strat()) {
+            assert_eq!(Range::<NumberVersion>::strictly_between(v1, v2), Range::between_exclusive(v1, v2));
+            assert_eq!(Range::<NumberVersion>::from_range_exclusive(v1, v2), Range::between_exclusive(v1, v2));
+            assert_eq!(Range::<NumberVersion>::at_most_exclusive(v1), Range::strictly_lower_than(v1));
+            assert_eq!(Range::<NumberVersion>::strictly_higher_than(v1), Range::higher_than_exclusive(v1));
+            assert_eq!(Range::<NumberVersion>::lower_than(v1), Range::at_most(v1));
+        }
+
+        #[test]
+        fn from_bounds_contains(lower in bound_strat(), upper in bound_strat(), v in version_strat()) {
+            use std::ops::RangeBounds;
+            let bounds = (lower, upper);
+            assert_eq!(Range::from_bounds(lower, upper).contains(&v), bounds.contains(&v));
+        }
+
+        // Testing Cargo requirements ----------------------
+
+        #[cfg(feature = "semver")]
+        #[test]
+        fn from_cargo_spec_matches_cargo(c1 in cargo_comparator_strat(), c2 in cargo_comparator_strat(), version in semver_strat()) {
+            for spec in [c1.clone(), format!("{}, {}", c1, c2)] {
+                let req = semver::VersionReq::parse(&spec).unwrap();
+                let range = Range::from_cargo_spec(&spec).unwrap();
+                assert_eq!(range.contains(&version), req.matches(&version), "{}", spec);
+            }
+        }
+
+        #[cfg(feature = "semver")]
+        #[test]
+        fn to_semver_req_string_round_trip(c1 in cargo_comparator_strat(), c2 in cargo_comparator_strat()) {
+            let range = Range::from_cargo_spec(&format!("{}, {}", c1, c2)).unwrap();
+            match range.to_semver_req_string() {
+                Ok(req) => {
+                    assert!(semver::VersionReq::parse(&req).is_ok(), "{}", req);
+                    assert_eq!(Range::from_cargo_spec(&req).unwrap(), range);
+                }
+                Err(ToSemverError::NotRepresentable) => assert_eq!(range, Range::none()),
+            }
+        }
+
+        // Testing volume ----------------------------------
+
+        #[test]
+        fn volume_counts_contained_versions(range in strategy()) {
+            let bounded = range.intersection(&Range::strictly_lower_than(1000));
+            let count = (0..1000).filter(|v| bounded.contains(&NumberVersion(*v))).count();
+            assert_eq!(bounded.volume(), Some(count as u64));
+        }
+
+        #[test]
+        fn volume_of_unbounded_is_none(v in version_strat()) {
+            assert_eq!(Range::higher_than(v).volume(), None);
+        }
+
+        // Testing serialization ---------------------------
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn postcard_round_trip(range in strategy()) {
+            let bytes = postcard::to_allocvec(&range).unwrap();
+            assert_eq!(postcard::from_bytes::<Range<NumberVersion>>(&bytes).unwrap(), range);
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn serde_string_round_trip(range in strategy()) {
+            let json = serde_json::to_string(&Named { range: range.clone() }).unwrap();
+            assert_eq!(json, serde_json::json!({ "range": range.to_string() }).to_string());
+            assert_eq!(serde_json::from_str::<Named>(&json).unwrap().range, range);
+        }
+
         // Testing contains --------------------------------
 
         #[test]
@@ -406,4 +2428,403 @@ pub mod tests {
             assert_eq!(range.contains(&version), range.intersection(&Range::exact(version)) != Range::none());
         }
     }
+
+    #[test]
+    fn volume_examples() {
+        assert_eq!(Range::<NumberVersion>::none().volume(), Some(0));
+        assert_eq!(Range::<NumberVersion>::exact(3).volume(), Some(1));
+        let range = Range::<NumberVersion>::between(2, 5).union(&Range::between(10, 12));
+        assert_eq!(range.volume(), Some(5));
+        assert_eq!(Range::<NumberVersion>::any().volume(), None);
+    }
+
+    #[test]
+    fn is_subset_of_examples() {
+        let none = Range::<NumberVersion>::none();
+        let any = Range::<NumberVersion>::any();
+        let between = |v1: u32, v2: u32| Range::<NumberVersion>::between(v1, v2);
+        assert!(none.is_subset_of(&none));
+        assert!(none.is_subset_of(&any));
+        assert!(!any.is_subset_of(&none));
+        assert!(any.is_subset_of(&any));
+        assert!(between(2, 4).is_subset_of(&between(1, 5)));
+        assert!(!between(0, 4).is_subset_of(&between(1, 5)));
+        assert!(!Range::<NumberVersion>::higher_than(2).is_subset_of(&between(1, 5)));
+        let gap = between(1, 3).union(&between(4, 6));
+        assert!(!between(2, 5).is_subset_of(&gap));
+        assert!(Range::<NumberVersion>::exact(5).is_subset_of(&gap));
+    }
+
+    #[test]
+    fn try_from_intervals() {
+        let v = NumberVersion;
+        assert_eq!(
+            Range::try_from(vec![(v(5), None), (v(1), Some(v(3))), (v(2), Some(v(4)))]),
+            Ok(Range::between(1, 4).union(&Range::higher_than(5)))
+        );
+        assert_eq!(
+            Range::try_from(vec![(v(1), Some(v(3))), (v(3), Some(v(3)))]),
+            Err(RangeError::EmptyInterval { index: 1 })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |s: &str| s.parse::<Range<NumberVersion>>();
+        assert_eq!(
+            parse("1 <= v < 3x"),
+            Err(RangeParseError::InvalidVersion {
+                range: "1 <= v < 3x".to_string(),
+                version: "3x".to_string(),
+                reason: "3x".parse::<u32>().unwrap_err().to_string(),
+            })
+        );
+        assert_eq!(
+            parse("1 = v"),
+            Err(RangeParseError::InvalidFormat {
+                range: "1 = v".to_string()
+            })
+        );
+        assert!(parse("[ 1, 3 [  [ 5, ∞").is_err());
+        assert!(parse("[ 3, 1 [").is_err());
+        assert_eq!(
+            parse("3 <= v < 1"),
+            Err(RangeParseError::InvalidFormat {
+                range: "3 <= v < 1".to_string()
+            })
+        );
+        assert_eq!(
+            parse("[ 1, 3 [  [ 5, ∞ ["),
+            Ok(Range::between(1, 3).union(&Range::higher_than(5)))
+        );
+    }
+
+    #[test]
+    fn provenance_flows_through_operations() {
+        fn tags(range: &Range<NumberVersion>) -> Vec<&str> {
+            range.provenance().collect()
+        }
+        let r1 = Range::higher_than(1).with_provenance("a");
+        let r2 = Range::strictly_lower_than(5).with_provenance("b");
+        assert_eq!(r1, Range::higher_than(1));
+        assert_eq!(tags(&r1.negate()), vec!["a"]);
+        assert_eq!(tags(&r1.intersection(&r2)), vec!["a", "b"]);
+        assert_eq!(tags(&r2.union(&r1)), vec!["b", "a"]);
+        assert_eq!(
+            tags(&r1.intersection(&r1.clone().with_provenance("a"))),
+            vec!["a"]
+        );
+        assert_eq!(tags(&r1.intersection(&Range::exact(3))), vec!["a"]);
+        let mut range = Range::between(0, 3);
+        range.intersect_assign(&r2);
+        assert_eq!(tags(&range), vec!["b"]);
+        assert!(Range::<NumberVersion>::any().provenance().next().is_none());
+    }
+
+    #[test]
+    fn highest_version() {
+        assert_eq!(Range::<NumberVersion>::none().highest_version(), None);
+        assert_eq!(
+            Range::<NumberVersion>::higher_than(3).highest_version(),
+            Some(Unbounded)
+        );
+        let range = Range::<NumberVersion>::between(1, 3).union(&Range::between(5, 8));
+        assert_eq!(range.highest_version(), Some(Excluded(NumberVersion(8))));
+        assert!(!range.contains(&NumberVersion(8)));
+    }
+
+    #[test]
+    fn simplify_examples() {
+        let available: Vec<NumberVersion> = vec![1.into(), 3.into(), 5.into()];
+        let simplify = |range: Range<NumberVersion>| range.simplify(&available);
+        assert_eq!(simplify(Range::between(2, 4)), Range::between(2, 5));
+        assert_eq!(simplify(Range::between(3, 4)), Range::between(2, 5));
+        assert_eq!(simplify(Range::exact(1)), Range::strictly_lower_than(3));
+        assert_eq!(simplify(Range::higher_than(4)), Range::higher_than(4));
+        assert_eq!(simplify(Range::between(6, 8)), Range::none());
+        assert_eq!(simplify(Range::between(0, 10)), Range::any());
+        assert_eq!(
+            simplify(Range::exact(1).union(&Range::exact(5))),
+            Range::strictly_lower_than(3).union(&Range::higher_than(4))
+        );
+    }
+
+    /// Write ranges like npm requirements.
+    struct Comparators;
+
+    impl RangeFormatter<NumberVersion> for Comparators {
+        fn fmt_range(
+            &self,
+            range: &Range<NumberVersion>,
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let segments: Vec<_> = range
+                .iter_segments()
+                .map(|(start, end)| {
+                    let start = match start {
+                        Included(v) => Some(format!(">={}", v)),
+                        Excluded(v) => Some(format!(">{}", v)),
+                        Unbounded => None,
+                    };
+                    let end = match end {
+                        Included(v) => Some(format!("<={}", v)),
+                        Excluded(v) => Some(format!("<{}", v)),
+                        Unbounded => None,
+                    };
+                    let comparators: Vec<_> = start.into_iter().chain(end).collect();
+                    match comparators.as_slice() {
+                        [] => "*".to_string(),
+                        _ => comparators.join(" "),
+                    }
+                })
+                .collect();
+            write!(f, "{}", segments.join(" || "))
+        }
+    }
+
+    #[test]
+    fn display_with_formatter() {
+        let range = Range::<NumberVersion>::between(1, 3).union(&Range::higher_than(5));
+        assert_eq!(
+            range.display_with(&Comparators).to_string(),
+            ">=1 <3 || >=5"
+        );
+        let range = Range::<NumberVersion>::between(0, 2).union(&Range::between(4, 6));
+        assert_eq!(range.display_with(&Comparators).to_string(), "<2 || >=4 <6");
+        let any = Range::<NumberVersion>::any();
+        assert_eq!(any.display_with(&Comparators).to_string(), "*");
+        assert_eq!(
+            range.display_with(&DefaultRangeFormatter).to_string(),
+            range.to_string()
+        );
+    }
+
+    #[test]
+    fn bounding_interval() {
+        let range = Range::<NumberVersion>::between(1, 3).union(&Range::between(5, 7));
+        assert_eq!(
+            range.bounding_interval(),
+            Some((Included(1.into()), Excluded(7.into())))
+        );
+        let range = range.union(&Range::higher_than(9));
+        assert_eq!(
+            range.bounding_interval(),
+            Some((Included(1.into()), Unbounded))
+        );
+        assert_eq!(Range::<NumberVersion>::none().bounding_interval(), None);
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        let range = |v1, v2| Range::<SemanticVersion>::between(v1, v2);
+        assert_eq!(Range::caret((1, 2, 3)), range((1, 2, 3), (2, 0, 0)));
+        assert_eq!(Range::caret((0, 2, 3)), range((0, 2, 3), (0, 3, 0)));
+        assert_eq!(Range::caret((0, 0, 3)), range((0, 0, 3), (0, 0, 4)));
+        assert_eq!(Range::caret((0, 0, 0)), range((0, 0, 0), (0, 0, 1)));
+        assert_eq!(Range::tilde((1, 2, 3)), range((1, 2, 3), (1, 3, 0)));
+        assert_eq!(Range::tilde((0, 0, 3)), range((0, 0, 3), (0, 1, 0)));
+        let max = u32::MAX;
+        assert_eq!(Range::caret((max, 0, 0)), Range::higher_than((max, 0, 0)));
+        assert_eq!(Range::caret((0, max, 1)), range((0, max, 1), (1, 0, 0)));
+        assert_eq!(Range::caret((0, 0, max)), range((0, 0, max), (0, 1, 0)));
+        assert_eq!(Range::tilde((1, max, 0)), range((1, max, 0), (2, 0, 0)));
+        assert_eq!(
+            Range::tilde((max, max, 0)),
+            Range::higher_than((max, max, 0))
+        );
+        #[cfg(feature = "semver-syntax")]
+        for v in ["1.2.3", "0.2.3", "0.0.3", "0.0.0"] {
+            let version: SemanticVersion = v.parse().unwrap();
+            let req = |op| Range::parse_requirement(&format!("{}{}", op, v)).unwrap();
+            assert_eq!(Range::caret(version), req("^"));
+            assert_eq!(Range::tilde(version), req("~"));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_ranges_are_valid() {
+        use arbitrary::{Arbitrary, Unstructured};
+        let bytes: Vec<u8> = (0..=255).cycle().step_by(7).take(1024).collect();
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let range = Range::<NumberVersion>::arbitrary(&mut u).unwrap();
+            let rebuilt: Range<_> = range.iter_segments().map(|(s, e)| (*s, *e)).collect();
+            assert_eq!(rebuilt, range);
+        }
+    }
+
+    #[test]
+    fn count_versions() {
+        assert_eq!(Range::<NumberVersion>::none().count_versions(), Some(0));
+        assert_eq!(Range::<NumberVersion>::exact(4).count_versions(), Some(1));
+        let range = Range::<NumberVersion>::between(1, 3).union(&Range::between(5, 8));
+        assert_eq!(range.count_versions(), Some(5));
+        assert_eq!(range.union(&Range::higher_than(10)).count_versions(), None);
+        // Semantic versions cannot be counted.
+        let range = Range::<SemanticVersion>::between((1, 0, 0), (2, 0, 0));
+        assert_eq!(range.count_versions(), None);
+        assert_eq!(Range::<SemanticVersion>::none().count_versions(), Some(0));
+    }
+
+    #[test]
+    fn from_constraints_examples() {
+        use CompOp::*;
+        let range = |c: &[(CompOp, u32)]| {
+            let c: Vec<_> = c.iter().map(|(op, v)| (*op, NumberVersion(*v))).collect();
+            Range::from_constraints(&c)
+        };
+        assert_eq!(range(&[]), Ok(Range::any()));
+        assert_eq!(range(&[(Gte, 1), (Lt, 4)]), Ok(Range::between(1, 4)));
+        assert_eq!(range(&[(Gt, 1), (Lte, 4)]), Ok(Range::between(2, 5)));
+        assert_eq!(range(&[(Eq, 3)]), Ok(Range::exact(3)));
+        assert_eq!(
+            range(&[(Gte, 1), (Lt, 4), (Ne, 2)]),
+            Ok(Range::between(1, 2).union(&Range::between(3, 4)))
+        );
+        assert_eq!(range(&[(Gt, 3), (Lt, 4)]), Err(RangeError::Unsatisfiable));
+        assert_eq!(range(&[(Eq, 3), (Ne, 3)]), Err(RangeError::Unsatisfiable));
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn from_cargo_spec_examples() {
+        let range = |s: &str| Range::from_cargo_spec(s).unwrap();
+        let v = |s: &str| semver::Version::parse(s).unwrap();
+        assert_eq!(range("*"), Range::any());
+        assert_eq!(range("^1.5.3"), Range::between(v("1.5.3"), v("2.0.0-0")));
+        assert_eq!(range("1.5.3"), range("^1.5.3"));
+        assert_eq!(range("^0.5.3"), Range::between(v("0.5.3"), v("0.6.0-0")));
+        assert_eq!(range("^0.0.3"), Range::between(v("0.0.3"), v("0.0.4-0")));
+        assert_eq!(range("^0.0"), Range::between(v("0.0.0"), v("0.1.0-0")));
+        assert_eq!(range("~0.5"), Range::between(v("0.5.0"), v("0.6.0-0")));
+        assert_eq!(range("~1"), Range::between(v("1.0.0"), v("2.0.0-0")));
+        assert_eq!(range("1.2.*"), Range::between(v("1.2.0"), v("1.3.0-0")));
+        assert_eq!(
+            range(">=1.0, <2.0"),
+            Range::between(v("1.0.0"), v("2.0.0-0"))
+        );
+        assert_eq!(range("=1.2.3-alpha"), Range::exact(v("1.2.3-alpha")));
+        assert!(range(">1.2.3-alpha").contains(&v("1.2.3-alpha.1")));
+        assert!(!range("<1.2").contains(&v("1.2.0-alpha")));
+    }
+
+    #[cfg(feature = "semver-syntax")]
+    #[test]
+    fn parse_requirement_examples() {
+        let range = |s: &str| Range::<SemanticVersion>::parse_requirement(s).unwrap();
+        let v = |major, minor, patch| SemanticVersion::new(major, minor, patch);
+        assert_eq!(range("*"), Range::any());
+        assert_eq!(range("^1.2.3"), Range::between(v(1, 2, 3), v(2, 0, 0)));
+        assert_eq!(range("^0.2.3"), Range::between(v(0, 2, 3), v(0, 3, 0)));
+        assert_eq!(range("~1.2"), Range::between(v(1, 2, 0), v(1, 3, 0)));
+        assert_eq!(range(">=1.0, <2.0"), Range::between(v(1, 0, 0), v(2, 0, 0)));
+        assert_eq!(range("1.*"), Range::between(v(1, 0, 0), v(2, 0, 0)));
+        assert_eq!(range(">1.2.3"), Range::higher_than(v(1, 2, 4)));
+        assert_eq!(range("<=1.2"), Range::strictly_lower_than(v(1, 3, 0)));
+        assert_eq!(range("=1.2.3"), Range::exact(v(1, 2, 3)));
+        // Only pre-releases of 1.2.4 are in that range.
+        assert_eq!(range(">1.2.3, <1.2.4"), Range::none());
+        assert!(Range::<SemanticVersion>::parse_requirement("^1.2.3-alpha").is_err());
+        let max = u32::MAX;
+        assert_eq!(range("^4294967295"), Range::higher_than(v(max, 0, 0)));
+        assert_eq!(range("<=4294967295"), Range::any());
+        assert_eq!(range(">4294967294"), Range::higher_than(v(max, 0, 0)));
+        assert!(Range::<SemanticVersion>::parse_requirement(">4294967295").is_err());
+        assert!(Range::<SemanticVersion>::parse_requirement("^4294967296").is_err());
+        assert!(Range::<SemanticVersion>::parse_requirement(">=1.0, <abc").is_err());
+        assert_eq!(
+            Range::<SemanticVersion>::parse_requirement(">=1.0, <2.0.0-alpha"),
+            Err(CargoSpecError::InvalidComparator {
+                full_spec: ">=1.0, <2.0.0-alpha".to_string(),
+                comparator: "<2.0.0-alpha".to_string(),
+                reason: "pre-releases are not supported".to_string(),
+            })
+        );
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn contains_prerelease_aware() {
+        let range = |s: &str| Range::from_cargo_spec(s).unwrap();
+        let contains = |s: &str, v: &str| {
+            range(s).contains_prerelease_aware(&semver::Version::parse(v).unwrap())
+        };
+        assert!(range("^1.0.0").contains(&semver::Version::parse("1.5.0-alpha").unwrap()));
+        assert!(!contains("^1.0.0", "1.5.0-alpha"));
+        assert!(contains("^1.0.0", "1.5.0"));
+        assert!(contains(">=1.2.3-alpha", "1.2.3-beta"));
+        assert!(!contains(">=1.2.3-alpha", "1.2.4-alpha"));
+        assert!(contains(">=1.2.3-alpha", "1.2.4"));
+        assert!(!contains(">1.2.3", "1.2.4-alpha"));
+        assert!(!contains("<2.0.0", "2.0.0-alpha"));
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn from_requirement_is_from_cargo_spec() {
+        for spec in ["*", "^1.2", "~0.5", ">=1.0, <2.0", "=1.2.3-alpha"] {
+            let req = semver::VersionReq::parse(spec).unwrap();
+            assert_eq!(Range::from_requirement(&req), Range::from_cargo_spec(spec));
+        }
+        let max = format!("^{}", u64::MAX);
+        let req = semver::VersionReq::parse(&max).unwrap();
+        assert_eq!(
+            Range::from_requirement(&req),
+            Err(CargoSpecError::InvalidComparator {
+                full_spec: max.clone(),
+                comparator: max,
+                reason: "version number is too large".to_owned(),
+            })
+        );
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn from_cargo_spec_errors() {
+        assert_eq!(
+            Range::from_cargo_spec(">=1.0, <abc"),
+            Err(CargoSpecError::InvalidComparator {
+                full_spec: ">=1.0, <abc".to_owned(),
+                comparator: "<abc".to_owned(),
+                reason: semver::Comparator::parse("<abc").unwrap_err().to_string(),
+            })
+        );
+        assert!(Range::from_cargo_spec("").is_err());
+        let max = u64::MAX;
+        assert_eq!(
+            Range::from_cargo_spec(&format!("^{}", max)),
+            Err(CargoSpecError::InvalidComparator {
+                full_spec: format!("^{}", max),
+                comparator: format!("^{}", max),
+                reason: "version number is too large".to_owned(),
+            })
+        );
+        assert!(Range::from_cargo_spec(&format!("~1.{}", max)).is_err());
+        assert!(Range::from_cargo_spec(&format!("^0.0.{}", max)).is_err());
+        assert_eq!(
+            Range::from_cargo_spec(&format!(">={}", max)),
+            Ok(Range::higher_than(semver::Version::new(max, 0, 0)))
+        );
+    }
+
+    #[cfg(feature = "semver")]
+    #[test]
+    fn to_semver_req_string_examples() {
+        let v = |s: &str| semver::Version::parse(s).unwrap();
+        let req = |r: Range<semver::Version>| r.to_semver_req_string();
+        assert_eq!(req(Range::any()), Ok("*".to_string()));
+        assert_eq!(req(Range::exact(v("1.2.3"))), Ok("=1.2.3".to_string()));
+        assert_eq!(
+            req(Range::higher_than(v("1.2.3"))),
+            Ok(">=1.2.3".to_string())
+        );
+        assert_eq!(
+            req(Range::between(v("1.0.0"), v("2.0.0"))),
+            Ok(">=1.0.0, <2.0.0".to_string())
+        );
+        let alternation = Range::exact(v("1.0.0")).union(&Range::exact(v("2.0.0")));
+        assert_eq!(req(alternation), Err(ToSemverError::NotRepresentable));
+        assert_eq!(req(Range::none()), Err(ToSemverError::NotRepresentable));
+    }
 }
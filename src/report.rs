@@ -3,15 +3,20 @@
 //! Build a report as clear as possible as to why
 //! dependency solving failed.
 
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 
 use crate::package::Package;
-use crate::range::Range;
+use crate::range::{DefaultRangeFormatter, Range, RangeFormatter};
 use crate::term::Term;
 use crate::type_aliases::Map;
 use crate::version::Version;
 
+mod graphviz;
+
+pub use graphviz::GraphvizIncompatibilityExporter;
+
 /// Reporter trait.
 pub trait Reporter<P: Package, V: Version> {
     /// Output type of the report.
@@ -25,6 +30,7 @@ pub trait Reporter<P: Package, V: Version> {
 /// Derivation tree resulting in the impossibility
 /// to solve the dependencies of our root package.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DerivationTree<P: Package, V: Version> {
     /// External incompatibility.
     External(External<P, V>),
@@ -35,6 +41,7 @@ pub enum DerivationTree<P: Package, V: Version> {
 /// Incompatibilities that are not derived from others,
 /// they have their own reason.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum External<P: Package, V: Version> {
     /// Initial incompatibility aiming at picking the root package for the first decision.
     NotRoot(P, V),
@@ -44,10 +51,31 @@ pub enum External<P: Package, V: Version> {
     UnavailableDependencies(P, Range<V>),
     /// Incompatibility coming from the dependencies of a given package.
     FromDependencyOf(P, Range<V>, P, Range<V>),
+    /// Incompatibility coming from the dependencies of the root package.
+    RequiredByRoot(P, Range<V>),
+}
+
+/// Kind of an incompatibility, with references to its packages and versions,
+/// to classify the nodes of a [DerivationTree] without matching all their data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncompatibilityKind<P, V> {
+    /// See [External::NotRoot].
+    NotRoot(P, V),
+    /// See [External::NoVersions].
+    NoVersions(P),
+    /// See [External::UnavailableDependencies].
+    UnavailableDependencies(P),
+    /// See [External::FromDependencyOf], with the dependent and dependency packages.
+    FromDependency(P, P),
+    /// See [External::RequiredByRoot], with the dependency package.
+    RequiredByRoot(P),
+    /// Incompatibility derived from two others.
+    Derived,
 }
 
 /// Incompatibility derived from two others.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Derived<P: Package, V: Version> {
     /// Terms of the incompatibility.
     pub terms: Map<P, Term<V>>,
@@ -64,6 +92,107 @@ pub struct Derived<P: Package, V: Version> {
 }
 
 impl<P: Package, V: Version> DerivationTree<P, V> {
+    /// Retrieve the external incompatibility if this tree is a leaf.
+    pub fn as_external(&self) -> Option<&External<P, V>> {
+        match self {
+            Self::External(external) => Some(external),
+            Self::Derived(_) => None,
+        }
+    }
+
+    /// Retrieve the derived incompatibility if this tree is not a leaf.
+    pub fn as_derived(&self) -> Option<&Derived<P, V>> {
+        match self {
+            Self::External(_) => None,
+            Self::Derived(derived) => Some(derived),
+        }
+    }
+
+    /// Kind of the incompatibility at the root of this tree.
+    pub fn kind(&self) -> IncompatibilityKind<&P, &V> {
+        match self {
+            Self::External(external) => external.kind(),
+            Self::Derived(_) => IncompatibilityKind::Derived,
+        }
+    }
+
+    /// Depth of the tree, where a tree made of a single
+    /// external incompatibility has a depth of 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::External(_) => 1,
+            Self::Derived(derived) => derived.depth(),
+        }
+    }
+
+    /// Terms of the incompatibility at the root of this tree.
+    pub fn terms(&self) -> Map<P, Term<V>> {
+        match self {
+            Self::External(external) => external.terms(),
+            Self::Derived(derived) => derived.terms.clone(),
+        }
+    }
+
+    /// Textual representation of the incompatibility at the root of this tree,
+    /// with all terms and their ranges.
+    pub fn format_long(&self) -> String {
+        match self {
+            Self::External(external) => external.to_string(),
+            Self::Derived(derived) => DefaultStringReporter::string_terms(&derived.terms),
+        }
+    }
+
+    /// Textual representation of the incompatibility at the root of this tree,
+    /// with only the names of the packages involved, like `"a ∧ b conflicts"`.
+    pub fn format_short(&self) -> String {
+        let mut packages: Vec<_> = self.terms().keys().map(|p| p.to_string()).collect();
+        packages.sort();
+        if packages.is_empty() {
+            "version solving failed".into()
+        } else {
+            packages.join(" ∧ ") + " conflicts"
+        }
+    }
+
+    /// Textual representation of the incompatibility at the root of this tree,
+    /// with the given verbosity.
+    pub fn format_with_style(&self, style: ReportStyle) -> String {
+        match style {
+            ReportStyle::Short => self.format_short(),
+            ReportStyle::Long => self.format_long(),
+        }
+    }
+
+    /// Build the structured explanation of this derivation tree.
+    /// Its [Display](fmt::Display) implementation is what
+    /// [DefaultStringReporter] outputs.
+    pub fn to_report(&self) -> Report<P, V> {
+        self.to_report_with(&DefaultRangeFormatter)
+    }
+
+    /// Same as [to_report](Self::to_report), with ranges written by the given formatter.
+    pub fn to_report_with(&self, formatter: &dyn RangeFormatter<V>) -> Report<P, V> {
+        match self {
+            Self::External(external) => Report {
+                lines: vec![ReportLine {
+                    indent: 0,
+                    kind: ReportLineKind::of(external),
+                    terms: external.terms(),
+                    line_ref: None,
+                    new_paragraph: false,
+                    text: external.display_with(formatter).to_string(),
+                }],
+            },
+            Self::Derived(derived) => {
+                let mut builder = ReportBuilder::new(formatter);
+                builder.build_recursive(derived);
+                Report {
+                    lines: builder.lines,
+                }
+            }
+        }
+    }
+
     /// Merge the [NoVersions](External::NoVersions) external incompatibilities
     /// with the other one they are matched with
     /// in a derived incompatibility.
@@ -134,27 +263,110 @@ impl<P: Package, V: Version> DerivationTree<P, V> {
                     )))
                 }
             }
+            DerivationTree::External(External::RequiredByRoot(p, r)) => Some(
+                DerivationTree::External(External::RequiredByRoot(p, r.union(&range))),
+            ),
+        }
+    }
+}
+
+/// Extract the external incompatibility of a leaf.
+/// Gives back the tree if it is derived.
+impl<P: Package, V: Version> TryFrom<DerivationTree<P, V>> for External<P, V> {
+    type Error = DerivationTree<P, V>;
+
+    fn try_from(tree: DerivationTree<P, V>) -> Result<Self, Self::Error> {
+        match tree {
+            DerivationTree::External(external) => Ok(external),
+            DerivationTree::Derived(_) => Err(tree),
+        }
+    }
+}
+
+/// Extract the derived incompatibility of a node.
+/// Gives back the tree if it is an external leaf.
+impl<P: Package, V: Version> TryFrom<DerivationTree<P, V>> for Derived<P, V> {
+    type Error = DerivationTree<P, V>;
+
+    fn try_from(tree: DerivationTree<P, V>) -> Result<Self, Self::Error> {
+        match tree {
+            DerivationTree::External(_) => Err(tree),
+            DerivationTree::Derived(derived) => Ok(derived),
+        }
+    }
+}
+
+impl<P: Package, V: Version> Derived<P, V> {
+    /// Depth of the derivation tree of this incompatibility.
+    pub fn depth(&self) -> usize {
+        1 + self.cause1.depth().max(self.cause2.depth())
+    }
+}
+
+impl<P: Package, V: Version> External<P, V> {
+    /// Kind of this external incompatibility.
+    pub fn kind(&self) -> IncompatibilityKind<&P, &V> {
+        match self {
+            Self::NotRoot(p, v) => IncompatibilityKind::NotRoot(p, v),
+            Self::NoVersions(p, _) => IncompatibilityKind::NoVersions(p),
+            Self::UnavailableDependencies(p, _) => IncompatibilityKind::UnavailableDependencies(p),
+            Self::FromDependencyOf(p, _, dep, _) => IncompatibilityKind::FromDependency(p, dep),
+            Self::RequiredByRoot(dep, _) => IncompatibilityKind::RequiredByRoot(dep),
+        }
+    }
+
+    /// Terms forbidden together by this external incompatibility.
+    fn terms(&self) -> Map<P, Term<V>> {
+        let mut terms = Map::default();
+        match self {
+            Self::NotRoot(p, v) => {
+                terms.insert(p.clone(), Term::Negative(Range::exact(v.clone())));
+            }
+            Self::NoVersions(p, r) | Self::UnavailableDependencies(p, r) => {
+                terms.insert(p.clone(), Term::Positive(r.clone()));
+            }
+            Self::FromDependencyOf(p, r, dep, r_dep) => {
+                terms.insert(p.clone(), Term::Positive(r.clone()));
+                terms.insert(dep.clone(), Term::Negative(r_dep.clone()));
+            }
+            Self::RequiredByRoot(dep, r_dep) => {
+                terms.insert(dep.clone(), Term::Negative(r_dep.clone()));
+            }
         }
+        terms
     }
 }
 
 impl<P: Package, V: Version> fmt::Display for External<P, V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(&DefaultRangeFormatter, f)
+    }
+}
+
+impl<P: Package, V: Version> External<P, V> {
+    /// Write this incompatibility, with ranges written by the given formatter.
+    fn fmt_with(
+        &self,
+        formatter: &dyn RangeFormatter<V>,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
         match self {
             Self::NotRoot(package, version) => {
                 write!(f, "we are solving dependencies of {} {}", package, version)
             }
             Self::NoVersions(package, range) => {
-                if range == &Range::any() {
+                if range.is_full() {
                     write!(f, "there is no available version for {}", package)
                 } else {
+                    let range = range.display_with(formatter);
                     write!(f, "there is no version of {} in {}", package, range)
                 }
             }
             Self::UnavailableDependencies(package, range) => {
-                if range == &Range::any() {
+                if range.is_full() {
                     write!(f, "dependencies of {} are unavailable", package)
                 } else {
+                    let range = range.display_with(formatter);
                     write!(
                         f,
                         "dependencies of {} at version {} are unavailable",
@@ -163,60 +375,412 @@ impl<P: Package, V: Version> fmt::Display for External<P, V> {
                 }
             }
             Self::FromDependencyOf(p, range_p, dep, range_dep) => {
-                if range_p == &Range::any() && range_dep == &Range::any() {
-                    write!(f, "{} depends on {}", p, dep)
-                } else if range_p == &Range::any() {
-                    write!(f, "{} depends on {} {}", p, dep, range_dep)
-                } else if range_dep == &Range::any() {
-                    write!(f, "{} {} depends on {}", p, range_p, dep)
+                let (any_p, any_dep) = (range_p.is_full(), range_dep.is_full());
+                let from = provenance_suffix(range_dep);
+                let range_p = range_p.display_with(formatter);
+                let range_dep = range_dep.display_with(formatter);
+                match (any_p, any_dep) {
+                    (true, true) => write!(f, "{} depends on {}{}", p, dep, from),
+                    (true, false) => write!(f, "{} depends on {} {}{}", p, dep, range_dep, from),
+                    (false, true) => write!(f, "{} {} depends on {}{}", p, range_p, dep, from),
+                    (false, false) => write!(
+                        f,
+                        "{} {} depends on {} {}{}",
+                        p, range_p, dep, range_dep, from
+                    ),
+                }
+            }
+            Self::RequiredByRoot(package, range) => {
+                let from = provenance_suffix(range);
+                if range.is_full() {
+                    write!(f, "root requires {}{}", package, from)
                 } else {
-                    write!(f, "{} {} depends on {} {}", p, range_p, dep, range_dep)
+                    let range = range.display_with(formatter);
+                    write!(f, "root requires {} {}{}", package, range, from)
                 }
             }
         }
     }
+
+    /// Write this incompatibility, with ranges written by the given formatter.
+    fn display_with<'a>(&'a self, formatter: &'a dyn RangeFormatter<V>) -> impl fmt::Display + 'a {
+        struct ExternalDisplay<'a, P: Package, V: Version> {
+            external: &'a External<P, V>,
+            formatter: &'a dyn RangeFormatter<V>,
+        }
+        impl<'a, P: Package, V: Version> fmt::Display for ExternalDisplay<'a, P, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.external.fmt_with(self.formatter, f)
+            }
+        }
+        ExternalDisplay {
+            external: self,
+            formatter,
+        }
+    }
+}
+
+/// Provenance tags of a dependency range, written after it, like `" (from Cargo.toml:14)"`.
+fn provenance_suffix<V: Version>(range: &Range<V>) -> String {
+    let tags: Vec<_> = range.provenance().collect();
+    if tags.is_empty() {
+        String::new()
+    } else {
+        format!(" (from {})", tags.join(", "))
+    }
+}
+
+/// Verbosity of the textual representation of an incompatibility.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportStyle {
+    /// Only the names of the packages involved.
+    Short,
+    /// All terms with their ranges.
+    Long,
+}
+
+/// Structured explanation of a resolution failure,
+/// made of lines that tools can render with their own
+/// indentation, colors or wording.
+#[derive(Debug, Clone)]
+pub struct Report<P: Package, V: Version> {
+    lines: Vec<ReportLine<P, V>>,
+}
+
+/// One line of a [Report].
+#[derive(Debug, Clone)]
+pub struct ReportLine<P: Package, V: Version> {
+    /// Nesting level of the line.
+    /// Lines of the main chain of explanations are at level 0,
+    /// and explanations they refer to are one level deeper.
+    pub indent: usize,
+    /// Kind of reason introduced by this line.
+    pub kind: ReportLineKind,
+    /// Terms of the incompatibility concluded by this line.
+    pub terms: Map<P, Term<V>>,
+    /// Reference given to this line if later lines refer to it.
+    pub line_ref: Option<usize>,
+    /// Whether this line starts a new paragraph.
+    pub new_paragraph: bool,
+    /// Sentence of this line, without indentation nor reference.
+    pub text: String,
+}
+
+/// Kind of reason introduced by a [ReportLine].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportLineKind {
+    /// Conclusion drawn from previous lines, or a conflict between packages.
+    Conflict,
+    /// A package depends on another one.
+    Dependency,
+    /// There is no version of a package in some range.
+    NoVersions,
+    /// Dependencies of a package are unavailable.
+    UnavailableDependencies,
+}
+
+impl ReportLineKind {
+    fn of<P: Package, V: Version>(external: &External<P, V>) -> Self {
+        match external.kind() {
+            IncompatibilityKind::NoVersions(_) => Self::NoVersions,
+            IncompatibilityKind::UnavailableDependencies(_) => Self::UnavailableDependencies,
+            IncompatibilityKind::FromDependency(_, _) | IncompatibilityKind::RequiredByRoot(_) => {
+                Self::Dependency
+            }
+            IncompatibilityKind::NotRoot(_, _) | IncompatibilityKind::Derived => Self::Conflict,
+        }
+    }
+
+    /// Most specific kind of two external incompatibilities explained together.
+    fn of_both<P: Package, V: Version>(
+        external1: &External<P, V>,
+        external2: &External<P, V>,
+    ) -> Self {
+        let rank = |kind: &Self| match kind {
+            Self::Conflict => 0,
+            Self::Dependency => 1,
+            Self::UnavailableDependencies => 2,
+            Self::NoVersions => 3,
+        };
+        let (kind1, kind2) = (Self::of(external1), Self::of(external2));
+        if rank(&kind1) >= rank(&kind2) {
+            kind1
+        } else {
+            kind2
+        }
+    }
+}
+
+impl<P: Package, V: Version> Report<P, V> {
+    /// Lines of the report, in reading order.
+    pub fn lines(&self) -> &[ReportLine<P, V>] {
+        &self.lines
+    }
+}
+
+impl<P: Package, V: Version> fmt::Display for ReportLine<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:width$}{}", "", self.text, width = 2 * self.indent)?;
+        if let Some(line_ref) = self.line_ref {
+            write!(f, " ({})", line_ref)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: Package, V: Version> fmt::Display for Report<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            if line.new_paragraph {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
 }
 
 /// Default reporter able to generate an explanation as a [String].
-pub struct DefaultStringReporter {
+/// The explanation is the [Display](fmt::Display) of the [Report]
+/// of the derivation tree.
+pub struct DefaultStringReporter;
+
+/// Builder of the lines of a [Report].
+struct ReportBuilder<'a, P: Package, V: Version> {
+    /// Formatter of the ranges in the lines.
+    formatter: &'a dyn RangeFormatter<V>,
     /// Number of explanations already with a line reference.
     ref_count: usize,
     /// Shared nodes that have already been marked with a line reference.
     /// The incompatibility ids are the keys, and the line references are the values.
     shared_with_ref: Map<usize, usize>,
     /// Accumulated lines of the report already generated.
-    lines: Vec<String>,
+    lines: Vec<ReportLine<P, V>>,
+    /// Nesting level of the lines currently generated.
+    indent: usize,
+    /// Whether the next line starts a new paragraph.
+    new_paragraph: bool,
 }
 
 impl DefaultStringReporter {
-    /// Initialize the reporter.
-    fn new() -> Self {
+    /// Same as [report](Reporter::report), except that when the derivation tree
+    /// is deeper than `max_depth`, only its conclusion is reported.
+    /// Very deep trees make long explanations, that are hard to follow anyway.
+    pub fn report_with_max_depth<P: Package, V: Version>(
+        derivation_tree: &DerivationTree<P, V>,
+        max_depth: usize,
+    ) -> String {
+        let depth = derivation_tree.depth();
+        match derivation_tree {
+            DerivationTree::Derived(derived) if depth > max_depth => format!(
+                "{} (explanation omitted, its depth of {} is more than {})",
+                Self::string_terms(&derived.terms),
+                depth,
+                max_depth
+            ),
+            _ => <Self as Reporter<P, V>>::report(derivation_tree),
+        }
+    }
+
+    /// Same as [report](Reporter::report), with ranges written by the given formatter,
+    /// for example to write `">=1.2.0, <2.0.0"` instead of `"1.2.0 <= v < 2.0.0"`.
+    pub fn report_with_formatter<P: Package, V: Version>(
+        derivation_tree: &DerivationTree<P, V>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        derivation_tree.to_report_with(formatter).to_string()
+    }
+
+    // String explanations #####################################################
+
+    /// Simplest case, we just combine two external incompatibilities.
+    fn explain_both_external<P: Package, V: Version>(
+        external1: &External<P, V>,
+        external2: &External<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        // TODO: order should be chosen to make it more logical.
+        format!(
+            "Because {} and {}, {}.",
+            external1.display_with(formatter),
+            external2.display_with(formatter),
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// Both causes have already been explained so we use their refs.
+    fn explain_both_ref<P: Package, V: Version>(
+        ref_id1: usize,
+        derived1: &Derived<P, V>,
+        ref_id2: usize,
+        derived2: &Derived<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        // TODO: order should be chosen to make it more logical.
+        format!(
+            "Because {} ({}) and {} ({}), {}.",
+            Self::string_terms_with(&derived1.terms, formatter),
+            ref_id1,
+            Self::string_terms_with(&derived2.terms, formatter),
+            ref_id2,
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// One cause is derived (already explained so one-line),
+    /// the other is a one-line external cause,
+    /// and finally we conclude with the current incompatibility.
+    fn explain_ref_and_external<P: Package, V: Version>(
+        ref_id: usize,
+        derived: &Derived<P, V>,
+        external: &External<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        // TODO: order should be chosen to make it more logical.
+        format!(
+            "Because {} ({}) and {}, {}.",
+            Self::string_terms_with(&derived.terms, formatter),
+            ref_id,
+            external.display_with(formatter),
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// Add an external cause to the chain of explanations.
+    fn and_explain_external<P: Package, V: Version>(
+        external: &External<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        format!(
+            "And because {}, {}.",
+            external.display_with(formatter),
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// Add an already explained incompat to the chain of explanations.
+    fn and_explain_ref<P: Package, V: Version>(
+        ref_id: usize,
+        derived: &Derived<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        format!(
+            "And because {} ({}), {}.",
+            Self::string_terms_with(&derived.terms, formatter),
+            ref_id,
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// Add an already explained incompat to the chain of explanations.
+    fn and_explain_prior_and_external<P: Package, V: Version>(
+        prior_external: &External<P, V>,
+        external: &External<P, V>,
+        current_terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        format!(
+            "And because {} and {}, {}.",
+            prior_external.display_with(formatter),
+            external.display_with(formatter),
+            Self::string_terms_with(current_terms, formatter)
+        )
+    }
+
+    /// Try to print terms of an incompatibility in a human-readable way.
+    pub fn string_terms<P: Package, V: Version>(terms: &Map<P, Term<V>>) -> String {
+        Self::string_terms_with(terms, &DefaultRangeFormatter)
+    }
+
+    /// Same as [string_terms](Self::string_terms), with ranges written by the given formatter.
+    fn string_terms_with<P: Package, V: Version>(
+        terms: &Map<P, Term<V>>,
+        formatter: &dyn RangeFormatter<V>,
+    ) -> String {
+        let terms_vec: Vec<_> = terms.iter().collect();
+        match terms_vec.as_slice() {
+            [] => "version solving failed".into(),
+            // TODO: special case when that unique package is root.
+            [(package, Term::Positive(range))] if range.is_full() => {
+                format!("{} is forbidden", package)
+            }
+            [(package, Term::Positive(range))] => {
+                format!("{} {} is forbidden", package, range.display_with(formatter))
+            }
+            [(package, Term::Negative(range))] => {
+                format!("{} {} is mandatory", package, range.display_with(formatter))
+            }
+            [(p1, Term::Positive(r1)), (p2, Term::Negative(r2))] => {
+                External::FromDependencyOf(p1, r1.clone(), p2, r2.clone())
+                    .display_with(formatter)
+                    .to_string()
+            }
+            [(p1, Term::Negative(r1)), (p2, Term::Positive(r2))] => {
+                External::FromDependencyOf(p2, r2.clone(), p1, r1.clone())
+                    .display_with(formatter)
+                    .to_string()
+            }
+            slice => {
+                let str_terms: Vec<_> = slice
+                    .iter()
+                    .map(|(p, t)| match t {
+                        Term::Positive(r) => format!("{} {}", p, r.display_with(formatter)),
+                        Term::Negative(r) => {
+                            format!("{} Not ( {} )", p, r.display_with(formatter))
+                        }
+                    })
+                    .collect();
+                str_terms.join(", ") + " are incompatible"
+            }
+        }
+    }
+}
+
+impl<'a, P: Package, V: Version> ReportBuilder<'a, P, V> {
+    /// Initialize the builder.
+    fn new(formatter: &'a dyn RangeFormatter<V>) -> Self {
         Self {
+            formatter,
             ref_count: 0,
             shared_with_ref: Map::default(),
             lines: Vec::new(),
+            indent: 0,
+            new_paragraph: false,
         }
     }
 
-    fn build_recursive<P: Package, V: Version>(&mut self, derived: &Derived<P, V>) {
+    fn build_recursive(&mut self, derived: &Derived<P, V>) {
         self.build_recursive_helper(derived);
         if let Some(id) = derived.shared_id {
-            if self.shared_with_ref.get(&id) == None {
+            if !self.shared_with_ref.contains_key(&id) {
                 self.add_line_ref();
                 self.shared_with_ref.insert(id, self.ref_count);
             }
         };
     }
 
-    fn build_recursive_helper<P: Package, V: Version>(&mut self, current: &Derived<P, V>) {
+    fn build_recursive_helper(&mut self, current: &Derived<P, V>) {
         match (current.cause1.deref(), current.cause2.deref()) {
             (DerivationTree::External(external1), DerivationTree::External(external2)) => {
                 // Simplest case, we just combine two external incompatibilities.
-                self.lines.push(Self::explain_both_external(
-                    external1,
-                    external2,
+                self.push(
+                    ReportLineKind::of_both(external1, external2),
                     &current.terms,
-                ));
+                    DefaultStringReporter::explain_both_external(
+                        external1,
+                        external2,
+                        &current.terms,
+                        self.formatter,
+                    ),
+                );
             }
             (DerivationTree::Derived(derived), DerivationTree::External(external)) => {
                 // One cause is derived, so we explain this first
@@ -235,45 +799,76 @@ impl DefaultStringReporter {
                 ) {
                     // If both causes already have been referenced (shared_id),
                     // the explanation simply uses those references.
-                    (Some(ref1), Some(ref2)) => self.lines.push(Self::explain_both_ref(
-                        ref1,
-                        derived1,
-                        ref2,
-                        derived2,
+                    (Some(ref1), Some(ref2)) => self.push(
+                        ReportLineKind::Conflict,
                         &current.terms,
-                    )),
+                        DefaultStringReporter::explain_both_ref(
+                            ref1,
+                            derived1,
+                            ref2,
+                            derived2,
+                            &current.terms,
+                            self.formatter,
+                        ),
+                    ),
                     // Otherwise, if one only has a line number reference,
                     // we recursively call the one without reference and then
                     // add the one with reference to conclude.
                     (Some(ref1), None) => {
                         self.build_recursive(derived2);
-                        self.lines
-                            .push(Self::and_explain_ref(ref1, derived1, &current.terms));
+                        self.push(
+                            ReportLineKind::Conflict,
+                            &current.terms,
+                            DefaultStringReporter::and_explain_ref(
+                                ref1,
+                                derived1,
+                                &current.terms,
+                                self.formatter,
+                            ),
+                        );
                     }
                     (None, Some(ref2)) => {
                         self.build_recursive(derived1);
-                        self.lines
-                            .push(Self::and_explain_ref(ref2, derived2, &current.terms));
+                        self.push(
+                            ReportLineKind::Conflict,
+                            &current.terms,
+                            DefaultStringReporter::and_explain_ref(
+                                ref2,
+                                derived2,
+                                &current.terms,
+                                self.formatter,
+                            ),
+                        );
                     }
                     // Finally, if no line reference exists yet,
-                    // we call recursively the first one and then,
+                    // we call recursively the first one, one level deeper, and then,
                     //   - if this was a shared node, it will get a line ref
                     //     and we can simply recall this with the current node.
                     //   - otherwise, we add a line reference to it,
                     //     recursively call on the second node,
                     //     and finally conclude.
                     (None, None) => {
+                        self.indent += 1;
                         self.build_recursive(derived1);
-                        if derived1.shared_id != None {
-                            self.lines.push("".into());
+                        self.indent -= 1;
+                        if derived1.shared_id.is_some() {
+                            self.new_paragraph = true;
                             self.build_recursive(current);
                         } else {
                             self.add_line_ref();
                             let ref1 = self.ref_count;
-                            self.lines.push("".into());
+                            self.new_paragraph = true;
                             self.build_recursive(derived2);
-                            self.lines
-                                .push(Self::and_explain_ref(ref1, derived1, &current.terms));
+                            self.push(
+                                ReportLineKind::Conflict,
+                                &current.terms,
+                                DefaultStringReporter::and_explain_ref(
+                                    ref1,
+                                    derived1,
+                                    &current.terms,
+                                    self.formatter,
+                                ),
+                            );
                         }
                     }
                 }
@@ -285,25 +880,30 @@ impl DefaultStringReporter {
     ///
     /// The result will depend on the fact that the derived incompatibility
     /// has already been explained or not.
-    fn report_one_each<P: Package, V: Version>(
+    fn report_one_each(
         &mut self,
         derived: &Derived<P, V>,
         external: &External<P, V>,
         current_terms: &Map<P, Term<V>>,
     ) {
         match self.line_ref_of(derived.shared_id) {
-            Some(ref_id) => self.lines.push(Self::explain_ref_and_external(
-                ref_id,
-                derived,
-                external,
+            Some(ref_id) => self.push(
+                ReportLineKind::of(external),
                 current_terms,
-            )),
+                DefaultStringReporter::explain_ref_and_external(
+                    ref_id,
+                    derived,
+                    external,
+                    current_terms,
+                    self.formatter,
+                ),
+            ),
             None => self.report_recurse_one_each(derived, external, current_terms),
         }
     }
 
     /// Report one derived (without a line ref yet) and one external.
-    fn report_recurse_one_each<P: Package, V: Version>(
+    fn report_recurse_one_each(
         &mut self,
         derived: &Derived<P, V>,
         external: &External<P, V>,
@@ -314,153 +914,64 @@ impl DefaultStringReporter {
             // we can chain the external explanations.
             (DerivationTree::Derived(prior_derived), DerivationTree::External(prior_external)) => {
                 self.build_recursive(prior_derived);
-                self.lines.push(Self::and_explain_prior_and_external(
-                    prior_external,
-                    external,
+                self.push(
+                    ReportLineKind::of_both(prior_external, external),
                     current_terms,
-                ));
+                    DefaultStringReporter::and_explain_prior_and_external(
+                        prior_external,
+                        external,
+                        current_terms,
+                        self.formatter,
+                    ),
+                );
             }
             // If the derived cause has itself one external prior cause,
             // we can chain the external explanations.
             (DerivationTree::External(prior_external), DerivationTree::Derived(prior_derived)) => {
                 self.build_recursive(prior_derived);
-                self.lines.push(Self::and_explain_prior_and_external(
-                    prior_external,
-                    external,
+                self.push(
+                    ReportLineKind::of_both(prior_external, external),
                     current_terms,
-                ));
+                    DefaultStringReporter::and_explain_prior_and_external(
+                        prior_external,
+                        external,
+                        current_terms,
+                        self.formatter,
+                    ),
+                );
             }
             _ => {
                 self.build_recursive(derived);
-                self.lines
-                    .push(Self::and_explain_external(external, current_terms));
+                self.push(
+                    ReportLineKind::of(external),
+                    current_terms,
+                    DefaultStringReporter::and_explain_external(
+                        external,
+                        current_terms,
+                        self.formatter,
+                    ),
+                );
             }
         }
     }
 
-    // String explanations #####################################################
-
-    /// Simplest case, we just combine two external incompatibilities.
-    fn explain_both_external<P: Package, V: Version>(
-        external1: &External<P, V>,
-        external2: &External<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        // TODO: order should be chosen to make it more logical.
-        format!(
-            "Because {} and {}, {}.",
-            external1,
-            external2,
-            Self::string_terms(current_terms)
-        )
-    }
-
-    /// Both causes have already been explained so we use their refs.
-    fn explain_both_ref<P: Package, V: Version>(
-        ref_id1: usize,
-        derived1: &Derived<P, V>,
-        ref_id2: usize,
-        derived2: &Derived<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        // TODO: order should be chosen to make it more logical.
-        format!(
-            "Because {} ({}) and {} ({}), {}.",
-            Self::string_terms(&derived1.terms),
-            ref_id1,
-            Self::string_terms(&derived2.terms),
-            ref_id2,
-            Self::string_terms(current_terms)
-        )
-    }
-
-    /// One cause is derived (already explained so one-line),
-    /// the other is a one-line external cause,
-    /// and finally we conclude with the current incompatibility.
-    fn explain_ref_and_external<P: Package, V: Version>(
-        ref_id: usize,
-        derived: &Derived<P, V>,
-        external: &External<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        // TODO: order should be chosen to make it more logical.
-        format!(
-            "Because {} ({}) and {}, {}.",
-            Self::string_terms(&derived.terms),
-            ref_id,
-            external,
-            Self::string_terms(current_terms)
-        )
-    }
-
-    /// Add an external cause to the chain of explanations.
-    fn and_explain_external<P: Package, V: Version>(
-        external: &External<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        format!(
-            "And because {}, {}.",
-            external,
-            Self::string_terms(current_terms)
-        )
-    }
-
-    /// Add an already explained incompat to the chain of explanations.
-    fn and_explain_ref<P: Package, V: Version>(
-        ref_id: usize,
-        derived: &Derived<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        format!(
-            "And because {} ({}), {}.",
-            Self::string_terms(&derived.terms),
-            ref_id,
-            Self::string_terms(current_terms)
-        )
-    }
-
-    /// Add an already explained incompat to the chain of explanations.
-    fn and_explain_prior_and_external<P: Package, V: Version>(
-        prior_external: &External<P, V>,
-        external: &External<P, V>,
-        current_terms: &Map<P, Term<V>>,
-    ) -> String {
-        format!(
-            "And because {} and {}, {}.",
-            prior_external,
-            external,
-            Self::string_terms(current_terms)
-        )
-    }
+    // Helper functions ########################################################
 
-    /// Try to print terms of an incompatibility in a human-readable way.
-    pub fn string_terms<P: Package, V: Version>(terms: &Map<P, Term<V>>) -> String {
-        let terms_vec: Vec<_> = terms.iter().collect();
-        match terms_vec.as_slice() {
-            [] => "version solving failed".into(),
-            // TODO: special case when that unique package is root.
-            [(package, Term::Positive(range))] => format!("{} {} is forbidden", package, range),
-            [(package, Term::Negative(range))] => format!("{} {} is mandatory", package, range),
-            [(p1, Term::Positive(r1)), (p2, Term::Negative(r2))] => {
-                External::FromDependencyOf(p1, r1.clone(), p2, r2.clone()).to_string()
-            }
-            [(p1, Term::Negative(r1)), (p2, Term::Positive(r2))] => {
-                External::FromDependencyOf(p2, r2.clone(), p1, r1.clone()).to_string()
-            }
-            slice => {
-                let str_terms: Vec<_> = slice.iter().map(|(p, t)| format!("{} {}", p, t)).collect();
-                str_terms.join(", ") + " are incompatible"
-            }
-        }
+    fn push(&mut self, kind: ReportLineKind, terms: &Map<P, Term<V>>, text: String) {
+        self.lines.push(ReportLine {
+            indent: self.indent,
+            kind,
+            terms: terms.clone(),
+            line_ref: None,
+            new_paragraph: std::mem::take(&mut self.new_paragraph),
+            text,
+        });
     }
 
-    // Helper functions ########################################################
-
     fn add_line_ref(&mut self) {
-        let new_count = self.ref_count + 1;
-        self.ref_count = new_count;
+        self.ref_count += 1;
         if let Some(line) = self.lines.last_mut() {
-            *line = format!("{} ({})", line, new_count);
+            line.line_ref = Some(self.ref_count);
         }
     }
 
@@ -473,13 +984,6 @@ impl<P: Package, V: Version> Reporter<P, V> for DefaultStringReporter {
     type Output = String;
 
     fn report(derivation_tree: &DerivationTree<P, V>) -> Self::Output {
-        match derivation_tree {
-            DerivationTree::External(external) => external.to_string(),
-            DerivationTree::Derived(derived) => {
-                let mut reporter = Self::new();
-                reporter.build_recursive(derived);
-                reporter.lines.join("\n")
-            }
-        }
+        derivation_tree.to_report().to_string()
     }
 }
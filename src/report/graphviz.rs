@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Export of derivation trees in the DOT format of Graphviz,
+//! for debugging purposes.
+
+use std::fmt::Write;
+
+use crate::package::Package;
+use crate::report::{DefaultStringReporter, DerivationTree, IncompatibilityKind};
+use crate::type_aliases::Map;
+use crate::version::Version;
+
+/// Exporter of the implication graph of the incompatibilities of a derivation tree,
+/// where every derived incompatibility points to its two causes.
+/// Nodes are colored by kind: red for no versions, green for dependencies,
+/// blue for derived incompatibilities and black for the others.
+pub struct GraphvizIncompatibilityExporter;
+
+impl GraphvizIncompatibilityExporter {
+    /// Write the DOT graph of a derivation tree.
+    /// Incompatibilities shared by multiple derivations are written once.
+    pub fn export<P: Package, V: Version>(tree: &DerivationTree<P, V>) -> String {
+        let mut dot = String::from("digraph incompatibilities {\n");
+        Self::export_node(tree, &mut Map::default(), &mut 0, &mut dot);
+        dot.push('}');
+        dot
+    }
+
+    /// Write the node of a tree and the ones of its causes,
+    /// and return the id of that node.
+    fn export_node<P: Package, V: Version>(
+        tree: &DerivationTree<P, V>,
+        shared_nodes: &mut Map<usize, usize>,
+        node_count: &mut usize,
+        dot: &mut String,
+    ) -> usize {
+        let shared_id = match tree {
+            DerivationTree::External(_) => None,
+            DerivationTree::Derived(derived) => derived.shared_id,
+        };
+        if let Some(node) = shared_id.and_then(|id| shared_nodes.get(&id)) {
+            return *node;
+        }
+        let node = *node_count;
+        *node_count += 1;
+        if let Some(id) = shared_id {
+            shared_nodes.insert(id, node);
+        }
+        let color = match tree.kind() {
+            IncompatibilityKind::NoVersions(_) => "red",
+            IncompatibilityKind::FromDependency(..) | IncompatibilityKind::RequiredByRoot(_) => {
+                "green"
+            }
+            IncompatibilityKind::Derived => "blue",
+            _ => "black",
+        };
+        let label = match tree {
+            DerivationTree::External(external) => external.to_string(),
+            DerivationTree::Derived(derived) => DefaultStringReporter::string_terms(&derived.terms),
+        };
+        let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(dot, "  {} [label=\"{}\", color={}];", node, label, color).unwrap();
+        if let DerivationTree::Derived(derived) = tree {
+            for cause in [&derived.cause1, &derived.cause2].iter() {
+                let cause = Self::export_node(cause, shared_nodes, node_count, dot);
+                writeln!(dot, "  {} -> {};", node, cause).unwrap();
+            }
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PubGrubError;
+    use crate::range::Range;
+    use crate::solver::{resolve, OfflineDependencyProvider};
+    use crate::version::NumberVersion;
+
+    #[test]
+    fn export_derivation_tree() {
+        let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+        dependency_provider.add_dependencies("root", 0, vec![("a", Range::any())]);
+        let tree = match resolve(&dependency_provider, "root", 0) {
+            Err(PubGrubError::NoSolution(tree)) => tree,
+            _ => panic!("a has no version"),
+        };
+        let dot = GraphvizIncompatibilityExporter::export(&tree);
+        assert!(dot.starts_with("digraph incompatibilities {\n"));
+        assert!(dot.ends_with('}'));
+        assert!(dot.contains("  0 [label=\"root 0 is forbidden\", color=blue];"));
+        assert!(dot.contains("color=green"));
+        assert!(dot.contains("color=red"));
+        assert!(dot.contains("  0 -> 1;"));
+        assert!(dot.contains("  0 -> 2;"));
+    }
+}
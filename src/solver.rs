@@ -31,12 +31,8 @@
 //! [Package](crate::package::Package) is strictly equivalent and automatically generated
 //! for any type that implement [Clone] + [Eq] + [Hash] + [Debug] + [Display](std::fmt::Display).
 //! [Version](crate::version::Version) simply states that versions are ordered,
-//! that there should be
-//! a minimal [lowest](crate::version::Version::lowest) version (like 0.0.0 in semantic versions),
-//! and that for any version, it is possible to compute
-//! what the next version closest to this one is ([bump](crate::version::Version::bump)).
-//! For semantic versions, [bump](crate::version::Version::bump) corresponds to
-//! an increment of the patch number.
+//! and that there should be
+//! a minimal [lowest](crate::version::Version::lowest) version (like 0.0.0 in semantic versions).
 //!
 //! ## API
 //!
@@ -68,15 +64,28 @@
 use std::borrow::Borrow;
 use std::collections::{BTreeMap, BTreeSet as Set};
 use std::error::Error;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::error::PubGrubError;
 use crate::internal::core::State;
 use crate::internal::incompatibility::Incompatibility;
 use crate::package::Package;
 use crate::range::Range;
+use crate::report::{DerivationTree, External};
 use crate::type_aliases::{Map, SelectedDependencies};
 use crate::version::Version;
 
+#[cfg(feature = "async")]
+pub mod async_solver;
+#[cfg(feature = "tracing")]
+pub mod tracing_provider;
+mod workspace;
+
+pub use workspace::{resolve_workspace, WorkspacePackage};
+
 /// Main function of the library.
 /// Finds a set of packages satisfying dependency bounds for a given package + version pair.
 pub fn resolve<P: Package, V: Version>(
@@ -84,13 +93,269 @@ pub fn resolve<P: Package, V: Version>(
     package: P,
     version: impl Into<V>,
 ) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    resolve_configured(
+        dependency_provider,
+        package,
+        version,
+        SolverConfig::default(),
+    )
+}
+
+/// Same as [resolve], but stops the solver with
+/// [PubGrubError::LimitExceeded] once `max_decisions` decisions have been made.
+/// This protects against pathological cases or misconfigured dependency providers
+/// that would make the solver run for a very long time.
+pub fn resolve_with_limit<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+    max_decisions: u64,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    let config = SolverConfig::default().with_max_decisions(max_decisions);
+    resolve_configured(dependency_provider, package, version, config)
+}
+
+/// Optional parameters of the solver, given to [resolve_configured].
+/// The default configuration sets no limit.
+///
+/// The timeout, deadline and cancellation flag are checked
+/// once per iteration of the main loop of the solver,
+/// before the unit propagation of the last chosen package.
+/// So the solver stops at most one unit propagation and one call to
+/// [choose_package_version](DependencyProvider::choose_package_version)
+/// and [get_dependencies](DependencyProvider::get_dependencies)
+/// after they are triggered.
+/// If those calls can be slow, the dependency provider can also
+/// check them in its [should_cancel](DependencyProvider::should_cancel) method.
+///
+/// Preferences about which versions to pick are not part of it,
+/// since they are the responsibility of the [DependencyProvider].
+/// See for example [VersionStrategy] for the [OfflineDependencyProvider].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct SolverConfig {
+    /// Stop the solver with [PubGrubError::Timeout]
+    /// once it ran for this duration.
+    pub timeout: Option<Duration>,
+    /// Stop the solver with [PubGrubError::LimitExceeded]
+    /// once it made that many decisions.
+    pub max_decisions: Option<u64>,
+    /// Stop the solver with [PubGrubError::Timeout]
+    /// once this instant is passed.
+    pub deadline: Option<Instant>,
+    /// Stop the solver with [PubGrubError::Cancelled]
+    /// once this flag is set, for example from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Fail with [PubGrubError::Cycle] if the selected packages
+    /// depend on each other in a cycle.
+    /// Cycles are allowed by default, since they do not prevent finding a solution.
+    pub forbid_cycles: bool,
+}
+
+impl SolverConfig {
+    /// Stop the solver with [PubGrubError::Timeout]
+    /// once it ran for this duration.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop the solver with [PubGrubError::LimitExceeded]
+    /// once it made that many decisions.
+    pub fn with_max_decisions(mut self, max_decisions: u64) -> Self {
+        self.max_decisions = Some(max_decisions);
+        self
+    }
+
+    /// Stop the solver with [PubGrubError::Timeout]
+    /// once this instant is passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop the solver with [PubGrubError::Cancelled]
+    /// once this flag is set.
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Fail with [PubGrubError::Cycle] if the selected packages
+    /// depend on each other in a cycle.
+    pub fn with_forbid_cycles(mut self, forbid_cycles: bool) -> Self {
+        self.forbid_cycles = forbid_cycles;
+        self
+    }
+}
+
+/// Statistics about the work done by the solver,
+/// returned by [resolve_with_stats].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveStats {
+    /// Number of versions picked as decisions.
+    pub decisions: usize,
+    /// Number of terms derived by unit propagation.
+    pub derivations: usize,
+    /// Number of backtracks after a conflict.
+    pub backtracks: usize,
+    /// Number of incompatibilities added,
+    /// from dependencies or learned during conflict resolution.
+    pub incompatibilities_added: usize,
+    /// Highest decision level reached.
+    pub max_decision_level: u32,
+}
+
+/// Same as [resolve], with the optional parameters of the solver
+/// given by a [SolverConfig].
+pub fn resolve_configured<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+    config: SolverConfig,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    let mut state = State::init(package.clone(), version.into());
+    solve(dependency_provider, &mut state, package, config)
+}
+
+/// Same as [resolve], but also returns statistics
+/// about the work done by the solver to find the solution.
+pub fn resolve_with_stats<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+) -> Result<(SelectedDependencies<P, V>, SolveStats), PubGrubError<P, V>> {
     let mut state = State::init(package.clone(), version.into());
-    let mut added_dependencies: Map<P, Set<V>> = Map::default();
+    let solution = solve(
+        dependency_provider,
+        &mut state,
+        package,
+        SolverConfig::default(),
+    )?;
+    Ok((solution, state.stats().clone()))
+}
+
+/// Same as [resolve], but keeps the versions pinned in a lock file
+/// as long as they do not prevent finding a solution.
+///
+/// Every pin is first a constraint on the exact version of its package.
+/// If there is no solution, the pins involved in the failure are relaxed,
+/// and the resolution starts again, until a solution is found
+/// or the failure does not involve any pin anymore.
+pub fn resolve_with_pins<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+    pins: &Map<P, V>,
+) -> Result<PinnedSolution<P, V>, PubGrubError<P, V>> {
+    let version = version.into();
+    let mut relaxed = Map::default();
+    loop {
+        let mut state = State::init(package.clone(), version.clone());
+        for (p, v) in pins.iter().filter(|(p, _)| !relaxed.contains_key(*p)) {
+            state.add_global_constraint(p.clone(), Range::exact(v.clone()));
+        }
+        match solve(
+            dependency_provider,
+            &mut state,
+            package.clone(),
+            SolverConfig::default(),
+        ) {
+            Ok(solution) => {
+                return Ok(PinnedSolution {
+                    solution,
+                    pins: pins.clone(),
+                    relaxed,
+                })
+            }
+            Err(PubGrubError::NoSolution(tree)) => {
+                let mut conflicting = Vec::new();
+                collect_conflicting_pins(&tree, pins, &mut conflicting);
+                conflicting.retain(|p| !relaxed.contains_key(p));
+                if conflicting.is_empty() {
+                    return Err(PubGrubError::NoSolution(tree));
+                }
+                for p in conflicting {
+                    log::info!("relaxing pin of {}", p);
+                    let v = pins[&p].clone();
+                    relaxed.insert(p, v);
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Pinned packages whose pin appears in the derivation tree of a failure.
+fn collect_conflicting_pins<P: Package, V: Version>(
+    tree: &DerivationTree<P, V>,
+    pins: &Map<P, V>,
+    conflicting: &mut Vec<P>,
+) {
+    match tree {
+        DerivationTree::External(External::NoVersions(p, range)) => {
+            if let Some(v) = pins.get(p) {
+                if range == &Range::exact(v.clone()).negate() && !conflicting.contains(p) {
+                    conflicting.push(p.clone());
+                }
+            }
+        }
+        DerivationTree::External(_) => {}
+        DerivationTree::Derived(derived) => {
+            collect_conflicting_pins(&derived.cause1, pins, conflicting);
+            collect_conflicting_pins(&derived.cause2, pins, conflicting);
+        }
+    }
+}
+
+/// Solution returned by [resolve_with_pins],
+/// remembering which pins could be kept.
+#[derive(Debug, Clone)]
+pub struct PinnedSolution<P: Package, V: Version> {
+    solution: SelectedDependencies<P, V>,
+    pins: Map<P, V>,
+    relaxed: Map<P, V>,
+}
+
+impl<P: Package, V: Version> PinnedSolution<P, V> {
+    /// The selected packages and versions.
+    pub fn solution(&self) -> &SelectedDependencies<P, V> {
+        &self.solution
+    }
+
+    /// Retrieve the selected packages and versions.
+    pub fn into_solution(self) -> SelectedDependencies<P, V> {
+        self.solution
+    }
+
+    /// Pins whose package was selected at the pinned version.
+    pub fn honored(&self) -> impl Iterator<Item = (&P, &V)> {
+        self.pins
+            .iter()
+            .filter(move |(p, v)| self.solution.get(*p) == Some(*v))
+    }
+
+    /// Pins that were relaxed because they prevented finding a solution.
+    pub fn relaxed(&self) -> impl Iterator<Item = (&P, &V)> {
+        self.relaxed.iter()
+    }
+}
+
+/// Main loop of the solver, starting from the root package.
+fn solve<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    state: &mut State<P, V>,
+    package: P,
+    config: SolverConfig,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    let mut solver_loop = SolverLoop::new(config);
     let mut next = package;
     loop {
         dependency_provider
             .should_cancel()
             .map_err(|err| PubGrubError::ErrorInShouldCancel(err))?;
+        solver_loop.check_limits()?;
 
         log::info!("unit_propagation: {}", &next);
         state.unit_propagation(next)?;
@@ -105,105 +370,280 @@ pub fn resolve<P: Package, V: Version>(
             // The borrow checker did not like using a match on potential_packages.
             // This `if ... is_none ... drop` is a workaround.
             // I believe this is a case where Polonius could help, when and if it lands in rustc.
-            return state.partial_solution.extract_solution().ok_or_else(|| {
-                PubGrubError::Failure(
-                    "How did we end up with no package to choose but no solution?".into(),
-                )
-            });
+            return solver_loop.extract_solution(state);
         }
         let decision = dependency_provider
             .choose_package_version(potential_packages.unwrap())
             .map_err(PubGrubError::ErrorChoosingPackageVersion)?;
         log::info!("DP chose: {} @ {:?}", decision.0, decision.1);
-        next = decision.0.clone();
+        let (chosen, version) = decision;
+        next = chosen.clone();
 
         // Pick the next compatible version.
-        let term_intersection = state
-            .partial_solution
-            .term_intersection_for_package(&next)
-            .expect("a package was chosen but we don't have a term.");
-        let v = match decision.1 {
-            None => {
-                let inc = Incompatibility::no_versions(next.clone(), term_intersection.clone());
-                state.add_incompatibility(inc);
-                continue;
-            }
-            Some(x) => x,
+        let v = match check_decision(state, &next, version)? {
+            None => continue,
+            Some(v) => v,
         };
-        if !term_intersection.contains(&v) {
-            return Err(PubGrubError::ErrorChoosingPackageVersion(
-                "choose_package_version picked an incompatible version".into(),
-            ));
-        }
 
-        if added_dependencies
-            .entry(next.clone())
-            .or_default()
-            .insert(v.clone())
-        {
+        if solver_loop.decide(&next, &v)? {
             // Retrieve that package dependencies.
             let p = &next;
-            let dependencies = match dependency_provider.get_dependencies(p, &v).map_err(|err| {
+            let dependencies = dependency_provider.get_dependencies(p, &v).map_err(|err| {
                 PubGrubError::ErrorRetrievingDependencies {
                     package: p.clone(),
                     version: v.clone(),
                     source: err,
                 }
-            })? {
-                Dependencies::Unknown => {
-                    state.add_incompatibility(Incompatibility::unavailable_dependencies(
-                        p.clone(),
-                        v.clone(),
-                    ));
-                    continue;
-                }
-                Dependencies::Known(x) => {
-                    if x.contains_key(p) {
-                        return Err(PubGrubError::SelfDependency {
-                            package: p.clone(),
-                            version: v.clone(),
-                        });
+            })?;
+            solver_loop.record_dependencies(p, &v, &dependencies);
+            add_dependencies_to_state(state, p, v, dependencies)?;
+        } else {
+            // `dep_incompats` are already in `incompatibilities` so we know there are not satisfied
+            // terms and can add the decision directly.
+            log::info!("add_decision (not first time): {} @ {}", &next, v);
+            state.add_decision(next.clone(), v);
+        }
+    }
+}
+
+/// Bookkeeping of the main loop of the solver, besides its [State]:
+/// the limits of its [SolverConfig], the versions whose dependencies were already added,
+/// and the dependency graph used to detect cycles.
+/// It is shared by the synchronous and asynchronous solvers,
+/// which only differ in how they call the dependency provider.
+pub(crate) struct SolverLoop<P: Package, V: Version> {
+    config: SolverConfig,
+    start: Instant,
+    decisions_made: u64,
+    added_dependencies: Map<P, Set<V>>,
+    // Only recorded when cycles are forbidden.
+    dependency_graph: Map<P, BTreeMap<V, Vec<P>>>,
+}
+
+impl<P: Package, V: Version> SolverLoop<P, V> {
+    /// Start the clock of the solver with the given configuration.
+    pub(crate) fn new(config: SolverConfig) -> Self {
+        Self {
+            config,
+            start: Instant::now(),
+            decisions_made: 0,
+            added_dependencies: Map::default(),
+            dependency_graph: Map::default(),
+        }
+    }
+
+    /// Stop the solver once its timeout or deadline is passed,
+    /// or once its cancellation flag is set.
+    pub(crate) fn check_limits(&self) -> Result<(), PubGrubError<P, V>> {
+        if let Some(timeout) = self.config.timeout {
+            let elapsed = self.start.elapsed();
+            if elapsed >= timeout {
+                return Err(PubGrubError::Timeout { elapsed });
+            }
+        }
+        if let Some(deadline) = self.config.deadline {
+            if Instant::now() >= deadline {
+                return Err(PubGrubError::Timeout {
+                    elapsed: self.start.elapsed(),
+                });
+            }
+        }
+        if let Some(cancel) = &self.config.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(PubGrubError::Cancelled);
+            }
+        }
+        Ok(())
+    }
+
+    /// Count the decision of a package version,
+    /// and stop the solver once the maximum number of decisions is reached.
+    /// Returns `true` if the dependencies of that version were never added,
+    /// and thus must be retrieved.
+    pub(crate) fn decide(&mut self, package: &P, version: &V) -> Result<bool, PubGrubError<P, V>> {
+        let max_decisions = self.config.max_decisions.unwrap_or(u64::MAX);
+        if self.decisions_made >= max_decisions {
+            return Err(PubGrubError::LimitExceeded {
+                decisions_made: self.decisions_made,
+            });
+        }
+        self.decisions_made += 1;
+        Ok(self
+            .added_dependencies
+            .entry(package.clone())
+            .or_default()
+            .insert(version.clone()))
+    }
+
+    /// Remember the dependencies of a package version,
+    /// to detect cycles in the solution if they are forbidden.
+    pub(crate) fn record_dependencies(
+        &mut self,
+        package: &P,
+        version: &V,
+        dependencies: &Dependencies<P, V>,
+    ) {
+        if self.config.forbid_cycles {
+            if let Dependencies::Known(dependencies) = dependencies {
+                self.dependency_graph
+                    .entry(package.clone())
+                    .or_default()
+                    .insert(version.clone(), dependencies.keys().cloned().collect());
+            }
+        }
+    }
+
+    /// Extract the solution once there is no package left to choose,
+    /// failing if cycles are forbidden and the solution contains one.
+    pub(crate) fn extract_solution(
+        &self,
+        state: &State<P, V>,
+    ) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+        let solution = extract_solution(state)?;
+        if self.config.forbid_cycles {
+            if let Some(path) = find_cycle(&solution, &self.dependency_graph) {
+                return Err(PubGrubError::Cycle { path });
+            }
+        }
+        Ok(solution)
+    }
+}
+
+/// Finds packages of the solution depending on each other in a cycle,
+/// using the dependencies of every selected package and version.
+fn find_cycle<P: Package, V: Version>(
+    solution: &SelectedDependencies<P, V>,
+    dependency_graph: &Map<P, BTreeMap<V, Vec<P>>>,
+) -> Option<Vec<(P, V)>> {
+    let dependencies = |p: &P| -> &[P] {
+        solution
+            .get(p)
+            .and_then(|v| dependency_graph.get(p)?.get(v))
+            .map_or(&[], |deps| deps.as_slice())
+    };
+    let mut done: rustc_hash::FxHashSet<&P> = rustc_hash::FxHashSet::default();
+    for start in solution.keys() {
+        if done.contains(start) {
+            continue;
+        }
+        // Depth first search, with the index of the next dependency to visit.
+        let mut path: Vec<(&P, usize)> = vec![(start, 0)];
+        while let Some(&(p, i)) = path.last() {
+            match dependencies(p).get(i) {
+                Some(dep) => {
+                    path.last_mut().unwrap().1 += 1;
+                    if let Some(pos) = path.iter().position(|(q, _)| *q == dep) {
+                        return Some(
+                            path[pos..]
+                                .iter()
+                                .map(|(q, _)| ((*q).clone(), solution[*q].clone()))
+                                .collect(),
+                        );
                     }
-                    if let Some((dependent, _)) = x.iter().find(|(_, r)| r == &&Range::none()) {
-                        return Err(PubGrubError::DependencyOnTheEmptySet {
-                            package: p.clone(),
-                            version: v.clone(),
-                            dependent: dependent.clone(),
-                        });
+                    if !done.contains(dep) {
+                        path.push((dep, 0));
                     }
-                    x
                 }
-            };
+                None => {
+                    done.insert(p);
+                    path.pop();
+                }
+            }
+        }
+    }
+    None
+}
 
-            // Add that package and version if the dependencies are not problematic.
-            let dep_incompats =
-                state.add_incompatibility_from_dependencies(p.clone(), v.clone(), &dependencies);
+/// Extracts the solution once there is no package left to choose.
+fn extract_solution<P: Package, V: Version>(
+    state: &State<P, V>,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    log::debug!(
+        "Incompatibility store memory: {} bytes",
+        state.total_incompatibility_memory()
+    );
+    state.partial_solution.extract_solution().ok_or_else(|| {
+        PubGrubError::Failure("How did we end up with no package to choose but no solution?".into())
+    })
+}
 
-            // TODO: I don't think this check can actually happen.
-            // We might want to put it under #[cfg(debug_assertions)].
-            if state.incompatibility_store[dep_incompats.clone()]
-                .iter()
-                .any(|incompat| state.is_terminal(incompat))
-            {
-                // For a dependency incompatibility to be terminal,
-                // it can only mean that root depend on not root?
-                return Err(PubGrubError::Failure(
-                    "Root package depends on itself at a different version?".into(),
-                ));
+/// Checks the version chosen by the dependency provider for the chosen package.
+/// Returns `None` if there is no such version,
+/// after recording that no version matches the current constraints.
+pub(crate) fn check_decision<P: Package, V: Version>(
+    state: &mut State<P, V>,
+    package: &P,
+    version: Option<V>,
+) -> Result<Option<V>, PubGrubError<P, V>> {
+    let term_intersection = state
+        .partial_solution
+        .term_intersection_for_package(package)
+        .expect("a package was chosen but we don't have a term.");
+    let v = match version {
+        None => {
+            let inc = Incompatibility::no_versions(package.clone(), term_intersection.clone());
+            state.add_incompatibility(inc);
+            return Ok(None);
+        }
+        Some(x) => x,
+    };
+    if !term_intersection.contains(&v) {
+        return Err(PubGrubError::ErrorChoosingPackageVersion(
+            "choose_package_version picked an incompatible version".into(),
+        ));
+    }
+    Ok(Some(v))
+}
+
+/// Adds the dependencies of a package at the version picked for the first time,
+/// and that version as a decision if the dependencies are not problematic.
+pub(crate) fn add_dependencies_to_state<P: Package, V: Version>(
+    state: &mut State<P, V>,
+    p: &P,
+    v: V,
+    dependencies: Dependencies<P, V>,
+) -> Result<(), PubGrubError<P, V>> {
+    let dependencies = match dependencies {
+        Dependencies::Unknown => {
+            state.add_incompatibility(Incompatibility::unavailable_dependencies(p.clone(), v));
+            return Ok(());
+        }
+        Dependencies::Known(x) => {
+            if x.contains_key(p) {
+                return Err(PubGrubError::SelfDependency {
+                    package: p.clone(),
+                    version: v,
+                });
             }
-            state.partial_solution.add_version(
-                p.clone(),
-                v,
-                dep_incompats,
-                &state.incompatibility_store,
-            );
-        } else {
-            // `dep_incompats` are already in `incompatibilities` so we know there are not satisfied
-            // terms and can add the decision directly.
-            log::info!("add_decision (not first time): {} @ {}", &next, v);
-            state.partial_solution.add_decision(next.clone(), v);
+            if let Some((dependent, _)) = x.iter().find(|(_, r)| r == &&Range::none()) {
+                return Err(PubGrubError::DependencyOnTheEmptySet {
+                    package: p.clone(),
+                    version: v,
+                    dependent: dependent.clone(),
+                });
+            }
+            x
         }
+    };
+
+    // Add that package and version if the dependencies are not problematic.
+    let dep_incompats =
+        state.add_incompatibility_from_dependencies(p.clone(), v.clone(), &dependencies);
+
+    // TODO: I don't think this check can actually happen.
+    // We might want to put it under #[cfg(debug_assertions)].
+    if dep_incompats
+        .iter()
+        .any(|&id| state.is_terminal(&state.incompatibility_store[id]))
+    {
+        // For a dependency incompatibility to be terminal,
+        // it can only mean that root depend on not root?
+        return Err(PubGrubError::Failure(
+            "Root package depends on itself at a different version?".into(),
+        ));
     }
+    state.add_version(p.clone(), v, &dep_incompats);
+    Ok(())
 }
 
 /// An enum used by [DependencyProvider] that holds information about package dependencies.
@@ -224,6 +664,65 @@ pub enum Dependencies<P: Package, V: Version> {
 /// while the latter means they could not be fetched by [DependencyProvider].
 pub type DependencyConstraints<P, V> = Map<P, Range<V>>;
 
+/// Builder of a list of dependencies, to be given to
+/// [add_dependencies](OfflineDependencyProvider::add_dependencies).
+///
+/// ```
+/// # use pubgrub::solver::{Constraints, OfflineDependencyProvider};
+/// # use pubgrub::version::NumberVersion;
+/// let mut dependency_provider = OfflineDependencyProvider::<&str, NumberVersion>::new();
+/// let dependencies = Constraints::new()
+///     .at_least("menu", 1)
+///     .exact("icons", 2)
+///     .build();
+/// dependency_provider.add_dependencies("root", 1, dependencies);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Constraints<P: Package, V: Version> {
+    constraints: Vec<(P, Range<V>)>,
+}
+
+impl<P: Package, V: Version> Constraints<P, V> {
+    /// Start with an empty list of dependencies.
+    pub fn new() -> Self {
+        Self {
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Require a version of the package in the given range.
+    pub fn require(&mut self, package: P, range: Range<V>) -> &mut Self {
+        self.constraints.push((package, range));
+        self
+    }
+
+    /// Require a version of the package higher or equal to the given one.
+    pub fn at_least(&mut self, package: P, version: impl Into<V>) -> &mut Self {
+        self.require(package, Range::higher_than(version))
+    }
+
+    /// Require exactly that version of the package.
+    pub fn exact(&mut self, package: P, version: impl Into<V>) -> &mut Self {
+        self.require(package, Range::exact(version))
+    }
+
+    /// Require a version of the package strictly lower than the given one.
+    pub fn less_than(&mut self, package: P, version: impl Into<V>) -> &mut Self {
+        self.require(package, Range::strictly_lower_than(version))
+    }
+
+    /// Retrieve the list of dependencies.
+    pub fn build(&self) -> Vec<(P, Range<V>)> {
+        self.constraints.clone()
+    }
+}
+
+impl<P: Package, V: Version> Default for Constraints<P, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Trait that allows the algorithm to retrieve available packages and their dependencies.
 /// An implementor needs to be supplied to the [resolve] function.
 pub trait DependencyProvider<P: Package, V: Version> {
@@ -305,12 +804,53 @@ where
     (pkg, version)
 }
 
+/// Dependencies shared by all the versions of a range.
+type RangeDependencies<P, V> = (Range<V>, DependencyConstraints<P, V>);
+
 /// A basic implementation of [DependencyProvider].
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(transparent))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        transparent,
+        bound(
+            serialize = "P: serde::Serialize, V: serde::Serialize",
+            deserialize = "P: serde::Deserialize<'de>, V: serde::Deserialize<'de>"
+        )
+    )
+)]
 pub struct OfflineDependencyProvider<P: Package, V: Version> {
     dependencies: Map<P, BTreeMap<V, DependencyConstraints<P, V>>>,
+    /// Dependencies registered for ranges of versions that cannot be enumerated,
+    /// in the order they were registered.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    range_dependencies: Map<P, Vec<RangeDependencies<P, V>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    strategy: VersionStrategy<P, V>,
+}
+
+/// Strategy used by [OfflineDependencyProvider]
+/// to pick a version of the chosen package.
+#[derive(Debug, Clone)]
+pub enum VersionStrategy<P: Package, V: Version> {
+    /// Pick the oldest version compatible with the constraints.
+    Minimum,
+    /// Pick the newest version compatible with the constraints.
+    /// This is the default.
+    Maximum,
+    /// Pick the version closest to the one pinned for that package:
+    /// the pinned version itself, then newer ones, then older ones.
+    /// Packages without a pinned version are picked with the newest version.
+    Closest(Map<P, V>),
+}
+
+// Not derived since that would require P and V to implement Default.
+#[allow(clippy::derivable_impls)]
+impl<P: Package, V: Version> Default for VersionStrategy<P, V> {
+    fn default() -> Self {
+        Self::Maximum
+    }
 }
 
 impl<P: Package, V: Version> OfflineDependencyProvider<P, V> {
@@ -318,9 +858,17 @@ impl<P: Package, V: Version> OfflineDependencyProvider<P, V> {
     pub fn new() -> Self {
         Self {
             dependencies: Map::default(),
+            range_dependencies: Map::default(),
+            strategy: VersionStrategy::default(),
         }
     }
 
+    /// Use the given strategy to pick versions of packages.
+    pub fn with_strategy(mut self, strategy: VersionStrategy<P, V>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
     /// Registers the dependencies of a package and version pair.
     /// Dependencies must be added with a single call to
     /// [add_dependencies](OfflineDependencyProvider::add_dependencies).
@@ -347,6 +895,54 @@ impl<P: Package, V: Version> OfflineDependencyProvider<P, V> {
             .or_default() = package_deps;
     }
 
+    /// Registers the same dependencies for every version of a package
+    /// contained in the given range.
+    /// Just like with [add_dependencies](OfflineDependencyProvider::add_dependencies),
+    /// those dependencies replace the ones previously registered.
+    ///
+    /// When the versions of the range can be [counted](Range::count_versions),
+    /// like in a bounded range of [NumberVersion](crate::version::NumberVersion),
+    /// every one of them is registered.
+    /// Otherwise, the versions already registered are updated,
+    /// and the range is kept to provide the dependencies of the other versions it contains
+    /// in [get_dependencies](DependencyProvider::get_dependencies),
+    /// but those are not listed by [versions](OfflineDependencyProvider::versions).
+    pub fn add_dependencies_range<I: IntoIterator<Item = (P, Range<V>)>>(
+        &mut self,
+        package: P,
+        version_range: Range<V>,
+        dependencies: I,
+    ) {
+        let package_deps: DependencyConstraints<P, V> = dependencies.into_iter().collect();
+        if version_range.count_versions().is_some() {
+            let versions = self.dependencies.entry(package).or_default();
+            for (start, _) in version_range.iter_segments() {
+                let mut version = match start {
+                    Included(v) => Some(v.clone()),
+                    Excluded(v) => v.successor(),
+                    Unbounded => Some(V::lowest()),
+                };
+                while let Some(v) = version.filter(|v| version_range.contains(v)) {
+                    version = v.successor();
+                    versions.insert(v, package_deps.clone());
+                }
+            }
+            return;
+        }
+        if let Some(versions) = self.dependencies.get_mut(&package) {
+            for (_, deps) in versions
+                .iter_mut()
+                .filter(|(v, _)| version_range.contains(v))
+            {
+                *deps = package_deps.clone();
+            }
+        }
+        self.range_dependencies
+            .entry(package)
+            .or_default()
+            .push((version_range, package_deps));
+    }
+
     /// Lists packages that have been saved.
     pub fn packages(&self) -> impl Iterator<Item = &P> {
         self.dependencies.keys()
@@ -358,33 +954,152 @@ impl<P: Package, V: Version> OfflineDependencyProvider<P, V> {
         self.dependencies.get(package).map(|k| k.keys())
     }
 
+    /// Lists versions of saved packages in sorted order,
+    /// with no version if no information is available regarding that package.
+    pub fn versions_for<'a>(&'a self, package: &P) -> impl Iterator<Item = &'a V> + 'a {
+        self.dependencies
+            .get(package)
+            .into_iter()
+            .flat_map(|versions| versions.keys())
+    }
+
+    /// Check if that version of the package was saved.
+    pub fn contains(&self, package: &P, version: &V) -> bool {
+        self.dependencies
+            .get(package)
+            .is_some_and(|versions| versions.contains_key(version))
+    }
+
+    /// Size of the dependency graph, as the number of saved package and version pairs,
+    /// and the total number of dependencies of all of them.
+    pub fn graph_size(&self) -> (usize, usize) {
+        self.dependencies
+            .values()
+            .flat_map(|versions| versions.values())
+            .fold((0, 0), |(nodes, edges), deps| {
+                (nodes + 1, edges + deps.len())
+            })
+    }
+
+    /// Average number of dependencies of the saved package and version pairs.
+    /// Returns 0 if nothing was saved.
+    pub fn average_degree(&self) -> f64 {
+        match self.graph_size() {
+            (0, _) => 0.0,
+            (nodes, edges) => edges as f64 / nodes as f64,
+        }
+    }
+
     /// Lists dependencies of a given package and version.
     /// Returns [None] if no information is available regarding that package and version pair.
     fn dependencies(&self, package: &P, version: &V) -> Option<DependencyConstraints<P, V>> {
-        self.dependencies.get(package)?.get(version).cloned()
+        if let Some(deps) = self.dependencies.get(package).and_then(|v| v.get(version)) {
+            return Some(deps.clone());
+        }
+        let ranges = self.range_dependencies.get(package)?;
+        ranges
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(version))
+            .map(|(_, deps)| deps.clone())
+    }
+}
+
+#[cfg(feature = "json")]
+impl<P, V> OfflineDependencyProvider<P, V>
+where
+    P: Package + std::str::FromStr,
+    P::Err: std::fmt::Display,
+    V: Version + std::str::FromStr,
+    V::Err: std::fmt::Display,
+{
+    /// Read a registry in JSON, mapping every "package@version"
+    /// to its dependencies, given as ranges written like their [Display](std::fmt::Display).
+    ///
+    /// ```json
+    /// {
+    ///   "menu@1.0.0": { "dropdown": "1.0.0 <= v < 2.0.0" },
+    ///   "dropdown@1.0.0": {}
+    /// }
+    /// ```
+    pub fn from_json(reader: impl std::io::Read) -> Result<Self, serde_json::Error> {
+        use serde::de::Error;
+        let registry: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_reader(reader)?;
+        let mut provider = Self::new();
+        for (package_version, deps) in registry {
+            let (package, version) = package_version.rsplit_once('@').ok_or_else(|| {
+                serde_json::Error::custom(format!(
+                    "\"{}\" is not of the form \"package@version\"",
+                    package_version
+                ))
+            })?;
+            let package = package.parse().map_err(serde_json::Error::custom)?;
+            let version: V = version.parse().map_err(serde_json::Error::custom)?;
+            let dependencies = deps
+                .into_iter()
+                .map(|(dep, range)| {
+                    let dep = dep.parse().map_err(serde_json::Error::custom)?;
+                    let range = range.parse().map_err(serde_json::Error::custom)?;
+                    Ok((dep, range))
+                })
+                .collect::<Result<Vec<_>, serde_json::Error>>()?;
+            provider.add_dependencies(package, version, dependencies);
+        }
+        Ok(provider)
+    }
+
+    /// Write the registry in JSON, in the format read by [from_json](Self::from_json).
+    pub fn to_json(&self, writer: impl std::io::Write) -> Result<(), serde_json::Error> {
+        let registry: BTreeMap<String, BTreeMap<String, String>> = self
+            .dependencies
+            .iter()
+            .flat_map(|(package, versions)| {
+                versions.iter().map(move |(version, deps)| {
+                    let deps = deps
+                        .iter()
+                        .map(|(dep, range)| (dep.to_string(), range.to_string()))
+                        .collect();
+                    (format!("{}@{}", package, version), deps)
+                })
+            })
+            .collect();
+        serde_json::to_writer_pretty(writer, &registry)
     }
 }
 
 /// An implementation of [DependencyProvider] that
 /// contains all dependency information available in memory.
 /// Packages are picked with the fewest versions contained in the constraints first.
-/// Versions are picked with the newest versions first.
+/// Versions are picked according to the [VersionStrategy],
+/// with the newest versions first by default.
 impl<P: Package, V: Version> DependencyProvider<P, V> for OfflineDependencyProvider<P, V> {
     fn choose_package_version<T: Borrow<P>, U: Borrow<Range<V>>>(
         &self,
         potential_packages: impl Iterator<Item = (T, U)>,
     ) -> Result<(T, Option<V>), Box<dyn Error>> {
-        Ok(choose_package_with_fewest_versions(
-            |p| {
-                self.dependencies
-                    .get(p)
-                    .into_iter()
-                    .flat_map(|k| k.keys())
-                    .rev()
-                    .cloned()
-            },
-            potential_packages,
-        ))
+        let versions = |p: &P| self.dependencies.get(p).into_iter().flat_map(|k| k.keys());
+        Ok(match &self.strategy {
+            VersionStrategy::Minimum => {
+                choose_package_with_fewest_versions(|p| versions(p).cloned(), potential_packages)
+            }
+            VersionStrategy::Maximum => choose_package_with_fewest_versions(
+                |p| versions(p).rev().cloned(),
+                potential_packages,
+            ),
+            VersionStrategy::Closest(pins) => choose_package_with_fewest_versions(
+                |p| -> Box<dyn Iterator<Item = V>> {
+                    match (self.dependencies.get(p), pins.get(p)) {
+                        (Some(k), Some(pin)) => Box::new(
+                            k.range(pin.clone()..)
+                                .chain(k.range(..pin.clone()).rev())
+                                .map(|(v, _)| v.clone()),
+                        ),
+                        _ => Box::new(versions(p).rev().cloned()),
+                    }
+                },
+                potential_packages,
+            ),
+        })
     }
 
     fn get_dependencies(
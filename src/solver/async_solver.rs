@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Asynchronous version of the solver, for dependency providers
+//! that need to fetch data from the network, like package registries.
+//!
+//! The resolution algorithm is exactly the same as the one of [resolve](crate::solver::resolve),
+//! with the same [SolverConfig] limits, only the calls to the dependency provider are awaited.
+//! Those are the only suspension points, so the futures returned by the provider
+//! can be driven by any executor.
+//!
+//! This module is only available with the `async` feature.
+
+use std::borrow::Borrow;
+use std::error::Error;
+
+use crate::error::PubGrubError;
+use crate::internal::core::State;
+use crate::package::Package;
+use crate::range::Range;
+use crate::solver::{
+    add_dependencies_to_state, check_decision, Dependencies, DependencyProvider, SolveStats,
+    SolverConfig, SolverLoop,
+};
+use crate::type_aliases::SelectedDependencies;
+use crate::version::Version;
+
+/// Asynchronous counterpart of [DependencyProvider].
+///
+/// Every [DependencyProvider] is also an [AsyncDependencyProvider]
+/// whose futures are immediately ready.
+// The returned futures are not required to be Send,
+// so that providers can keep non thread-safe clients or caches.
+#[allow(async_fn_in_trait)]
+pub trait AsyncDependencyProvider<P: Package, V: Version> {
+    /// Decision making method, see [DependencyProvider::choose_package_version].
+    async fn choose_package_version<T: Borrow<P>, U: Borrow<Range<V>>>(
+        &self,
+        potential_packages: impl Iterator<Item = (T, U)>,
+    ) -> Result<(T, Option<V>), Box<dyn Error>>;
+
+    /// Retrieves the package dependencies, see [DependencyProvider::get_dependencies].
+    async fn get_dependencies(
+        &self,
+        package: &P,
+        version: &V,
+    ) -> Result<Dependencies<P, V>, Box<dyn Error>>;
+
+    /// Called fairly regularly during the resolution,
+    /// see [DependencyProvider::should_cancel].
+    fn should_cancel(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl<P: Package, V: Version, DP: DependencyProvider<P, V>> AsyncDependencyProvider<P, V> for DP {
+    async fn choose_package_version<T: Borrow<P>, U: Borrow<Range<V>>>(
+        &self,
+        potential_packages: impl Iterator<Item = (T, U)>,
+    ) -> Result<(T, Option<V>), Box<dyn Error>> {
+        DependencyProvider::choose_package_version(self, potential_packages)
+    }
+
+    async fn get_dependencies(
+        &self,
+        package: &P,
+        version: &V,
+    ) -> Result<Dependencies<P, V>, Box<dyn Error>> {
+        DependencyProvider::get_dependencies(self, package, version)
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn Error>> {
+        DependencyProvider::should_cancel(self)
+    }
+}
+
+/// Same as [resolve](crate::solver::resolve), with an [AsyncDependencyProvider].
+pub async fn async_resolve<P: Package, V: Version>(
+    dependency_provider: &impl AsyncDependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    async_resolve_configured(
+        dependency_provider,
+        package,
+        version,
+        SolverConfig::default(),
+    )
+    .await
+}
+
+/// Same as [resolve_configured](crate::solver::resolve_configured),
+/// with an [AsyncDependencyProvider].
+pub async fn async_resolve_configured<P: Package, V: Version>(
+    dependency_provider: &impl AsyncDependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+    config: SolverConfig,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    let mut state = State::init(package.clone(), version.into());
+    async_solve(dependency_provider, &mut state, package, config).await
+}
+
+/// Same as [resolve_with_stats](crate::solver::resolve_with_stats),
+/// with an [AsyncDependencyProvider].
+pub async fn async_resolve_with_stats<P: Package, V: Version>(
+    dependency_provider: &impl AsyncDependencyProvider<P, V>,
+    package: P,
+    version: impl Into<V>,
+) -> Result<(SelectedDependencies<P, V>, SolveStats), PubGrubError<P, V>> {
+    let mut state = State::init(package.clone(), version.into());
+    let solution = async_solve(
+        dependency_provider,
+        &mut state,
+        package,
+        SolverConfig::default(),
+    )
+    .await?;
+    Ok((solution, state.stats().clone()))
+}
+
+/// Main loop of the solver, awaiting the calls to the dependency provider.
+async fn async_solve<P: Package, V: Version>(
+    dependency_provider: &impl AsyncDependencyProvider<P, V>,
+    state: &mut State<P, V>,
+    package: P,
+    config: SolverConfig,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<P, V>> {
+    let mut solver_loop = SolverLoop::new(config);
+    let mut next = package;
+    loop {
+        dependency_provider
+            .should_cancel()
+            .map_err(|err| PubGrubError::ErrorInShouldCancel(err))?;
+        solver_loop.check_limits()?;
+
+        log::info!("unit_propagation: {}", &next);
+        state.unit_propagation(next)?;
+
+        let potential_packages = state.partial_solution.potential_packages();
+        if potential_packages.is_none() {
+            drop(potential_packages);
+            return solver_loop.extract_solution(state);
+        }
+        let decision = dependency_provider
+            .choose_package_version(potential_packages.unwrap())
+            .await
+            .map_err(PubGrubError::ErrorChoosingPackageVersion)?;
+        log::info!("DP chose: {} @ {:?}", decision.0, decision.1);
+        let (chosen, version) = decision;
+        next = chosen.clone();
+
+        let v = match check_decision(state, &next, version)? {
+            None => continue,
+            Some(v) => v,
+        };
+
+        if solver_loop.decide(&next, &v)? {
+            let dependencies = dependency_provider
+                .get_dependencies(&next, &v)
+                .await
+                .map_err(|err| PubGrubError::ErrorRetrievingDependencies {
+                    package: next.clone(),
+                    version: v.clone(),
+                    source: err,
+                })?;
+            solver_loop.record_dependencies(&next, &v, &dependencies);
+            add_dependencies_to_state(state, &next, v, dependencies)?;
+        } else {
+            log::info!("add_decision (not first time): {} @ {}", &next, v);
+            state.add_decision(next.clone(), v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{resolve, resolve_with_stats, OfflineDependencyProvider};
+    use crate::version::NumberVersion;
+    use futures::executor::block_on;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::task::Poll;
+
+    /// Returns control to the executor once, as a network request would.
+    async fn yield_once() {
+        let mut yielded = false;
+        futures::future::poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// In memory provider whose futures yield once before being ready,
+    /// like a registry reached through the network.
+    struct YieldingProvider(OfflineDependencyProvider<&'static str, NumberVersion>);
+
+    impl AsyncDependencyProvider<&'static str, NumberVersion> for YieldingProvider {
+        async fn choose_package_version<
+            T: Borrow<&'static str>,
+            U: Borrow<Range<NumberVersion>>,
+        >(
+            &self,
+            potential_packages: impl Iterator<Item = (T, U)>,
+        ) -> Result<(T, Option<NumberVersion>), Box<dyn Error>> {
+            yield_once().await;
+            DependencyProvider::choose_package_version(&self.0, potential_packages)
+        }
+
+        async fn get_dependencies(
+            &self,
+            package: &&'static str,
+            version: &NumberVersion,
+        ) -> Result<Dependencies<&'static str, NumberVersion>, Box<dyn Error>> {
+            yield_once().await;
+            DependencyProvider::get_dependencies(&self.0, package, version)
+        }
+    }
+
+    fn provider() -> OfflineDependencyProvider<&'static str, NumberVersion> {
+        let mut dependency_provider = OfflineDependencyProvider::new();
+        dependency_provider.add_dependencies("root", 1, vec![("foo", Range::between(1, 3))]);
+        dependency_provider.add_dependencies("foo", 1, vec![("bar", Range::exact(1))]);
+        dependency_provider.add_dependencies("foo", 2, vec![("bar", Range::exact(3))]);
+        dependency_provider.add_dependencies("bar", 1, vec![]);
+        dependency_provider.add_dependencies("bar", 2, vec![]);
+        dependency_provider
+    }
+
+    #[test]
+    fn same_solution_as_sync() {
+        let sync_solution = resolve(&provider(), "root", 1).unwrap();
+        let async_solution =
+            block_on(async_resolve(&YieldingProvider(provider()), "root", 1)).unwrap();
+        assert_eq!(sync_solution, async_solution);
+        let blanket_solution = block_on(async_resolve(&provider(), "root", 1)).unwrap();
+        assert_eq!(sync_solution, blanket_solution);
+    }
+
+    #[test]
+    fn same_stats_as_sync() {
+        let (_, sync_stats) = resolve_with_stats(&provider(), "root", 1).unwrap();
+        let (_, async_stats) = block_on(async_resolve_with_stats(
+            &YieldingProvider(provider()),
+            "root",
+            1,
+        ))
+        .unwrap();
+        assert_eq!(sync_stats, async_stats);
+    }
+
+    #[test]
+    fn configuration_is_applied() {
+        let config = SolverConfig::default().with_max_decisions(1);
+        let result = block_on(async_resolve_configured(
+            &YieldingProvider(provider()),
+            "root",
+            1,
+            config,
+        ));
+        assert!(matches!(
+            result,
+            Err(PubGrubError::LimitExceeded { decisions_made: 1 })
+        ));
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let config = SolverConfig::default().with_cancel(cancel);
+        let result = block_on(async_resolve_configured(&provider(), "root", 1, config));
+        assert!(matches!(result, Err(PubGrubError::Cancelled)));
+    }
+}
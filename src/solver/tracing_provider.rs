@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dependency provider wrapper instrumenting the calls made by the solver
+//! with [tracing] spans.
+//!
+//! This module is only available with the `tracing` feature.
+//!
+//! Every call to the wrapped provider is made inside an `INFO` span
+//! named after the method, with the following fields:
+//!
+//! | span                     | `package`         | `version`         | `outcome`                          |
+//! |--------------------------|-------------------|-------------------|------------------------------------|
+//! | `choose_package_version` | package chosen    | version chosen    | `chosen`, `no_version` or `error`  |
+//! | `get_dependencies`       | package queried   | version queried   | `known`, `unknown` or `error`      |
+//!
+//! Those can be used in filters, like `pubgrub[get_dependencies{package=foo}]=info`
+//! with the `EnvFilter` of tracing-subscriber.
+//! The duration of each call is the duration of its span.
+
+use std::borrow::Borrow;
+use std::error::Error;
+
+use tracing::field;
+
+use crate::package::Package;
+use crate::range::Range;
+use crate::solver::{Dependencies, DependencyProvider};
+use crate::version::Version;
+
+/// Wrapper around a [DependencyProvider]
+/// recording every call made by the solver in a [tracing] span.
+/// Results of the wrapped provider are forwarded unchanged.
+#[derive(Debug, Clone)]
+pub struct TracingDependencyProvider<DP> {
+    dependency_provider: DP,
+}
+
+impl<DP> TracingDependencyProvider<DP> {
+    /// Instrument the calls to the given provider.
+    pub fn new(dependency_provider: DP) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+
+    /// Retrieve the wrapped provider.
+    pub fn into_inner(self) -> DP {
+        self.dependency_provider
+    }
+}
+
+impl<P: Package, V: Version, DP: DependencyProvider<P, V>> DependencyProvider<P, V>
+    for TracingDependencyProvider<DP>
+{
+    fn choose_package_version<T: Borrow<P>, U: Borrow<Range<V>>>(
+        &self,
+        potential_packages: impl Iterator<Item = (T, U)>,
+    ) -> Result<(T, Option<V>), Box<dyn Error>> {
+        let span = tracing::info_span!(
+            "choose_package_version",
+            package = field::Empty,
+            version = field::Empty,
+            outcome = field::Empty,
+        );
+        let _enter = span.enter();
+        let decision = self
+            .dependency_provider
+            .choose_package_version(potential_packages);
+        match &decision {
+            Ok((package, version)) => {
+                span.record("package", field::display(package.borrow()));
+                match version {
+                    Some(version) => {
+                        span.record("version", field::display(version));
+                        span.record("outcome", "chosen");
+                    }
+                    None => {
+                        span.record("outcome", "no_version");
+                    }
+                }
+            }
+            Err(_) => {
+                span.record("outcome", "error");
+            }
+        }
+        decision
+    }
+
+    fn get_dependencies(
+        &self,
+        package: &P,
+        version: &V,
+    ) -> Result<Dependencies<P, V>, Box<dyn Error>> {
+        let span = tracing::info_span!(
+            "get_dependencies",
+            package = %package,
+            version = %version,
+            outcome = field::Empty,
+        );
+        let _enter = span.enter();
+        let dependencies = self.dependency_provider.get_dependencies(package, version);
+        let outcome = match &dependencies {
+            Ok(Dependencies::Known(_)) => "known",
+            Ok(Dependencies::Unknown) => "unknown",
+            Err(_) => "error",
+        };
+        span.record("outcome", outcome);
+        dependencies
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn Error>> {
+        self.dependency_provider.should_cancel()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{resolve, OfflineDependencyProvider};
+    use crate::version::NumberVersion;
+
+    #[test]
+    fn same_solution_with_spans() {
+        let mut dependency_provider = OfflineDependencyProvider::<&str, NumberVersion>::new();
+        dependency_provider.add_dependencies("root", 1, vec![("foo", Range::between(1, 3))]);
+        dependency_provider.add_dependencies("foo", 1, vec![]);
+        dependency_provider.add_dependencies("foo", 2, vec![("bar", Range::any())]);
+        let expected = resolve(&dependency_provider, "root", 1);
+
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(std::io::sink)
+            .finish();
+        let traced = TracingDependencyProvider::new(dependency_provider);
+        let solution =
+            tracing::subscriber::with_default(subscriber, || resolve(&traced, "root", 1).unwrap());
+        assert_eq!(solution, expected.unwrap());
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Resolution of the dependencies of multiple root packages at once,
+//! like the members of a workspace sharing the same dependency graph.
+
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::error::PubGrubError;
+use crate::package::Package;
+use crate::range::Range;
+use crate::report::DerivationTree;
+use crate::solver::{resolve, Dependencies, DependencyProvider};
+use crate::term::Term;
+use crate::type_aliases::{Map, SelectedDependencies};
+use crate::version::Version;
+
+/// Package used by [resolve_workspace]:
+/// either the virtual root of the workspace,
+/// depending on all its members, or a real package.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WorkspacePackage<P> {
+    /// The virtual root package, depending on every member of the workspace.
+    Root,
+    /// A real package.
+    Package(P),
+}
+
+impl<P: Display> Display for WorkspacePackage<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Root => write!(f, "root"),
+            Self::Package(p) => write!(f, "{}", p),
+        }
+    }
+}
+
+/// Finds a set of packages satisfying the dependencies
+/// of all the given root packages at the same time.
+///
+/// A virtual root package depending on each of the given packages
+/// at its exact version is used as the root of the resolution.
+/// It does not appear in the solution, but it does in the derivation tree
+/// of [PubGrubError::NoSolution], where it is displayed as "root",
+/// without any version, like in the requirements of
+/// [External::RequiredByRoot](crate::report::External::RequiredByRoot).
+pub fn resolve_workspace<P: Package, V: Version>(
+    dependency_provider: &impl DependencyProvider<P, V>,
+    roots: impl IntoIterator<Item = (P, V)>,
+) -> Result<SelectedDependencies<P, V>, PubGrubError<WorkspacePackage<P>, V>> {
+    let workspace = WorkspaceDependencyProvider {
+        dependency_provider,
+        roots: roots.into_iter().collect(),
+    };
+    let solution = match resolve(&workspace, WorkspacePackage::Root, V::lowest()) {
+        Err(PubGrubError::NoSolution(mut tree)) => {
+            hide_root_version(&mut tree);
+            return Err(PubGrubError::NoSolution(tree));
+        }
+        result => result?,
+    };
+    Ok(solution
+        .into_iter()
+        .filter_map(|(p, v)| match p {
+            WorkspacePackage::Root => None,
+            WorkspacePackage::Package(p) => Some((p, v)),
+        })
+        .collect())
+}
+
+/// Replace the version of the virtual root in the terms of a derivation tree
+/// by any version, since it is not chosen by the user.
+fn hide_root_version<P: Package, V: Version>(tree: &mut DerivationTree<WorkspacePackage<P>, V>) {
+    if let DerivationTree::Derived(derived) = tree {
+        if let Some(term @ Term::Positive(_)) = derived.terms.get_mut(&WorkspacePackage::Root) {
+            *term = Term::Positive(Range::any());
+        }
+        hide_root_version(&mut derived.cause1);
+        hide_root_version(&mut derived.cause2);
+    }
+}
+
+/// Dependency provider adding the virtual root of a workspace
+/// to another dependency provider.
+struct WorkspaceDependencyProvider<'a, P: Package, V: Version, DP> {
+    dependency_provider: &'a DP,
+    roots: Map<P, V>,
+}
+
+/// A real package given to the wrapped dependency provider,
+/// remembering its position among the potential packages.
+struct Indexed<'a, P>(usize, &'a P);
+
+impl<'a, P> Borrow<P> for Indexed<'a, P> {
+    fn borrow(&self) -> &P {
+        self.1
+    }
+}
+
+impl<'a, P: Package, V: Version, DP: DependencyProvider<P, V>>
+    DependencyProvider<WorkspacePackage<P>, V> for WorkspaceDependencyProvider<'a, P, V, DP>
+{
+    fn choose_package_version<T: Borrow<WorkspacePackage<P>>, U: Borrow<Range<V>>>(
+        &self,
+        potential_packages: impl Iterator<Item = (T, U)>,
+    ) -> Result<(T, Option<V>), Box<dyn Error>> {
+        let mut potential_packages: Vec<(T, U)> = potential_packages.collect();
+        if let Some(root) = potential_packages
+            .iter()
+            .position(|(p, _)| p.borrow() == &WorkspacePackage::Root)
+        {
+            return Ok((potential_packages.swap_remove(root).0, Some(V::lowest())));
+        }
+        let real_packages = potential_packages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (p, range))| match p.borrow() {
+                WorkspacePackage::Root => None,
+                WorkspacePackage::Package(p) => Some((Indexed(i, p), range.borrow())),
+            });
+        let (Indexed(i, _), version) = self
+            .dependency_provider
+            .choose_package_version(real_packages)?;
+        Ok((potential_packages.swap_remove(i).0, version))
+    }
+
+    fn get_dependencies(
+        &self,
+        package: &WorkspacePackage<P>,
+        version: &V,
+    ) -> Result<Dependencies<WorkspacePackage<P>, V>, Box<dyn Error>> {
+        let package = match package {
+            WorkspacePackage::Root => {
+                return Ok(Dependencies::Known(
+                    self.roots
+                        .iter()
+                        .map(|(p, v)| {
+                            (
+                                WorkspacePackage::Package(p.clone()),
+                                Range::exact(v.clone()),
+                            )
+                        })
+                        .collect(),
+                ))
+            }
+            WorkspacePackage::Package(p) => p,
+        };
+        Ok(
+            match self
+                .dependency_provider
+                .get_dependencies(package, version)?
+            {
+                Dependencies::Unknown => Dependencies::Unknown,
+                Dependencies::Known(dependencies) => Dependencies::Known(
+                    dependencies
+                        .into_iter()
+                        .map(|(p, range)| (WorkspacePackage::Package(p), range))
+                        .collect(),
+                ),
+            },
+        )
+    }
+
+    fn should_cancel(&self) -> Result<(), Box<dyn Error>> {
+        self.dependency_provider.should_cancel()
+    }
+}
@@ -9,6 +9,7 @@ use std::fmt;
 
 ///  A positive or negative expression regarding a set of versions.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Term<V: Version> {
     /// For example, "1.0.0 <= v < 2.0.0" is a positive expression
     /// that is evaluated true if a version is selected
@@ -100,7 +101,12 @@ impl<V: Version> Term<V> {
     /// Just like for sets, we say that t1 is a subset of t2
     /// if and only if t1 ∩ t2 = t1.
     pub(crate) fn subset_of(&self, other: &Term<V>) -> bool {
-        self == &self.intersection(other)
+        match (self, other) {
+            (Self::Positive(r1), Self::Positive(r2)) => r1.is_subset_of(r2),
+            (Self::Positive(r1), Self::Negative(r2)) => !r1.overlaps(r2),
+            (Self::Negative(r1), Self::Negative(r2)) => r2.is_subset_of(r1),
+            (Self::Negative(_), Self::Positive(_)) => self == &self.intersection(other),
+        }
     }
 }
 
@@ -120,7 +126,7 @@ pub(crate) enum Relation {
 }
 
 /// Relation between terms.
-impl<'a, V: 'a + Version> Term<V> {
+impl<V: Version> Term<V> {
     /// Check if a set of terms satisfies this term.
     ///
     /// We say that a set of terms S "satisfies" a term t
@@ -149,6 +155,17 @@ impl<'a, V: 'a + Version> Term<V> {
     /// Check if a set of terms satisfies or contradicts a given term.
     /// Otherwise the relation is inconclusive.
     pub(crate) fn relation_with(&self, other_terms_intersection: &Term<V>) -> Relation {
+        // The intersection of two positive terms does not need to be allocated.
+        if let (Self::Positive(r1), Self::Positive(r2)) = (self, other_terms_intersection) {
+            let full_intersection = r1.lazy_intersection(r2);
+            return if full_intersection == *r2 {
+                Relation::Satisfied
+            } else if full_intersection.is_empty() {
+                Relation::Contradicted
+            } else {
+                Relation::Inconclusive
+            };
+        }
         let full_intersection = self.intersection(other_terms_intersection);
         if &full_intersection == other_terms_intersection {
             Relation::Satisfied
@@ -166,6 +183,18 @@ impl<V: Version> AsRef<Term<V>> for Term<V> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, V: Version + arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Term<V> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let range = u.arbitrary()?;
+        if u.arbitrary()? {
+            Ok(Self::Positive(range))
+        } else {
+            Ok(Self::Negative(range))
+        }
+    }
+}
+
 // REPORT ######################################################################
 
 impl<V: Version + fmt::Display> fmt::Display for Term<V> {
@@ -180,16 +209,13 @@ impl<V: Version + fmt::Display> fmt::Display for Term<V> {
 // TESTS #######################################################################
 
 #[cfg(test)]
-pub mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::version::NumberVersion;
     use proptest::prelude::*;
 
     pub fn strategy() -> impl Strategy<Value = Term<NumberVersion>> {
-        prop_oneof![
-            crate::range::tests::strategy().prop_map(Term::Positive),
-            crate::range::tests::strategy().prop_map(Term::Negative),
-        ]
+        crate::proptest_support::arbitrary_term()
     }
 
     proptest! {
@@ -208,5 +234,12 @@ pub mod tests {
             }
         }
 
+        // Testing subsets ---------------------------------
+
+        #[test]
+        fn subset_of_is_intersection(term1 in strategy(), term2 in strategy()) {
+            assert_eq!(term1.subset_of(&term2), term1 == term1.intersection(&term2));
+        }
+
     }
 }
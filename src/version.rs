@@ -3,16 +3,34 @@
 //! Traits and implementations to create and compare versions.
 
 use std::fmt::{self, Debug, Display};
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 use thiserror::Error;
 
-/// Versions have a minimal version (a "0" version)
-/// and are ordered such that every version has a next one.
+/// Versions are totally ordered and have a minimal version (a "0" version).
+/// They do not need to know which version comes right after another one,
+/// so dense version spaces, like timestamps or strings, are supported too.
 pub trait Version: Clone + Ord + Debug + Display {
     /// Returns the lowest version.
     fn lowest() -> Self;
-    /// Returns the next version, the smallest strictly higher version.
-    fn bump(&self) -> Self;
+    /// Version coming right after this one, for discrete version types.
+    /// Ranges use it to write their bounds in a single way, so that
+    /// ranges containing the same versions are equal, like `1 < v` and `2 <= v`.
+    /// Returns [None] by default, which is always correct:
+    /// ranges are then only less compact.
+    /// It must return [None] when there is no such version,
+    /// for dense version types or for the highest version.
+    fn successor(&self) -> Option<Self> {
+        None
+    }
+    /// Number of versions from `a` included to `b` excluded,
+    /// for version types where versions can be counted.
+    /// Returns [None] by default.
+    fn approx_distance(a: &Self, b: &Self) -> Option<u64> {
+        let _ = (a, b);
+        None
+    }
 }
 
 /// Type for semantic versions: major.minor.patch.
@@ -44,6 +62,13 @@ impl<'de> serde::Deserialize<'de> for SemanticVersion {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SemanticVersion {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
 // Constructors
 impl SemanticVersion {
     /// Create a version with "major", "minor" and "patch" values.
@@ -219,17 +244,211 @@ impl Version for SemanticVersion {
     fn lowest() -> Self {
         Self::zero()
     }
-    fn bump(&self) -> Self {
-        self.bump_patch()
+    fn successor(&self) -> Option<Self> {
+        let (major, minor, patch) = (*self).into();
+        match (
+            patch.checked_add(1),
+            minor.checked_add(1),
+            major.checked_add(1),
+        ) {
+            (Some(patch), _, _) => Some(Self::new(major, minor, patch)),
+            (None, Some(minor), _) => Some(Self::new(major, minor, 0)),
+            (None, None, major) => major.map(|major| Self::new(major, 0, 0)),
+        }
+    }
+}
+
+/// Type for calendar versions: year.month.day, with an optional micro number
+/// for several releases the same day, like `2024.01.31` or `2024.01.31.2`.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct CalVerVersion {
+    year: u16,
+    month: u8,
+    day: u8,
+    micro: u32,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CalVerVersion {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{}", self))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CalVerVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl CalVerVersion {
+    /// Create a version with "year", "month", "day" and "micro" values.
+    /// `version = year.month.day.micro`
+    /// The date is not checked beyond the ranges of months and days,
+    /// so that no version is lower than [lowest](Version::lowest), `0.01.01`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the month is not between 1 and 12,
+    /// or if the day is not between 1 and 31.
+    pub fn new(year: u16, month: u8, day: u8, micro: u32) -> Self {
+        assert!((1..=12).contains(&month), "invalid month {}", month);
+        assert!((1..=31).contains(&day), "invalid day {}", day);
+        Self {
+            year,
+            month,
+            day,
+            micro,
+        }
+    }
+}
+
+/// Error creating [CalVerVersion] from [String].
+#[derive(Error, Debug, PartialEq)]
+pub enum CalVerParseError {
+    /// [CalVerVersion] must contain year, month, day, and optionally micro parts.
+    #[error("version {full_version} must contain 3 or 4 numbers separated by dot")]
+    WrongNumberOfParts {
+        /// [CalVerVersion] that was being parsed.
+        full_version: String,
+    },
+    /// A part of the version is not a number in the expected range.
+    #[error("invalid '{version_part}' in '{full_version}': {reason}")]
+    InvalidPart {
+        /// [CalVerVersion] that was being parsed.
+        full_version: String,
+        /// A version part where parsing failed.
+        version_part: String,
+        /// Reason why that part is not valid.
+        reason: String,
+    },
+}
+
+impl FromStr for CalVerVersion {
+    type Err = CalVerParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_part<T: FromStr + PartialOrd>(
+            s: &str,
+            part: &str,
+            bounds: std::ops::RangeInclusive<T>,
+        ) -> Result<T, CalVerParseError>
+        where
+            T::Err: Display,
+        {
+            let invalid = |reason: String| CalVerParseError::InvalidPart {
+                full_version: s.to_string(),
+                version_part: part.to_string(),
+                reason,
+            };
+            let value = part.parse::<T>().map_err(|e| invalid(e.to_string()))?;
+            if bounds.contains(&value) {
+                Ok(value)
+            } else {
+                Err(invalid("out of range".to_string()))
+            }
+        }
+
+        let parts: Vec<_> = s.split('.').collect();
+        let (year, month, day, micro) = match parts.as_slice() {
+            [year, month, day] => (year, month, day, None),
+            [year, month, day, micro] => (year, month, day, Some(micro)),
+            _ => {
+                return Err(Self::Err::WrongNumberOfParts {
+                    full_version: s.to_string(),
+                })
+            }
+        };
+        Ok(Self::new(
+            parse_part(s, year, 0..=u16::MAX)?,
+            parse_part(s, month, 1..=12)?,
+            parse_part(s, day, 1..=31)?,
+            match micro {
+                Some(micro) => parse_part(s, micro, 0..=u32::MAX)?,
+                None => 0,
+            },
+        ))
+    }
+}
+
+impl Display for CalVerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}.{:02}", self.year, self.month, self.day)?;
+        if self.micro > 0 {
+            write!(f, ".{}", self.micro)?;
+        }
+        Ok(())
+    }
+}
+
+// Calendar versions are ordered chronologically,
+// and by micro number for releases of the same day.
+impl Version for CalVerVersion {
+    fn lowest() -> Self {
+        Self::new(0, 1, 1, 0)
+    }
+    // The next day is not known without checking dates,
+    // so there is only a successor within the releases of a day.
+    fn successor(&self) -> Option<Self> {
+        let micro = self.micro.checked_add(1)?;
+        Some(Self { micro, ..*self })
     }
 }
 
+#[test]
+fn calendar_versions() {
+    let parse = |s: &str| s.parse::<CalVerVersion>().unwrap();
+    assert!(parse("2024.01.31") < parse("2024.02.01"));
+    assert!(parse("2024.12.31.7") < parse("2025.01.01"));
+    assert!(parse("2024.01.31") < parse("2024.01.31.1"));
+    assert_eq!(parse("2024.01.31"), parse("2024.1.31.0"));
+    assert_eq!(CalVerVersion::lowest(), parse("0.01.01"));
+    assert!(CalVerVersion::lowest() <= parse("0.01.01.0"));
+    assert_eq!(parse("2024.01.31.2").to_string(), "2024.01.31.2");
+    assert_eq!(parse("2024.1.5").to_string(), "2024.01.05");
+    assert!(matches!(
+        "2024.13.01".parse::<CalVerVersion>(),
+        Err(CalVerParseError::InvalidPart { .. })
+    ));
+    assert!(matches!(
+        "2024.01".parse::<CalVerVersion>(),
+        Err(CalVerParseError::WrongNumberOfParts { .. })
+    ));
+    for invalid in ["2024.0.0", "2024.01.00", "2024.00.31", "2024.01.32"] {
+        assert!(matches!(
+            invalid.parse::<CalVerVersion>(),
+            Err(CalVerParseError::InvalidPart { .. })
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "invalid day")]
+fn calendar_version_without_day() {
+    CalVerVersion::new(2024, 1, 0, 0);
+}
+
 /// Simplest versions possible, just a positive number.
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize,))]
 #[cfg_attr(feature = "serde", serde(transparent))]
 pub struct NumberVersion(pub u32);
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for NumberVersion {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
+
 // Convert an usize into a version.
 impl From<u32> for NumberVersion {
     fn from(v: u32) -> Self {
@@ -244,6 +463,14 @@ impl From<NumberVersion> for u32 {
     }
 }
 
+impl FromStr for NumberVersion {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
 impl Display for NumberVersion {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -254,7 +481,77 @@ impl Version for NumberVersion {
     fn lowest() -> Self {
         Self(0)
     }
-    fn bump(&self) -> Self {
-        Self(self.0 + 1)
+    fn successor(&self) -> Option<Self> {
+        self.0.checked_add(1).map(Self)
+    }
+    fn approx_distance(a: &Self, b: &Self) -> Option<u64> {
+        Some(u64::from(b.0.saturating_sub(a.0)))
     }
 }
+
+// Implement Version for the versions of the semver crate.
+// The lowest version is 0.0.0-0 since pre-release versions
+// are lower than their release, and "0" is the lowest pre-release identifier.
+#[cfg(feature = "semver")]
+impl Version for semver::Version {
+    fn lowest() -> Self {
+        let mut v = semver::Version::new(0, 0, 0);
+        v.pre = semver::Prerelease::new("0").unwrap();
+        v
+    }
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn semver_lowest_version() {
+    let v = |s: &str| semver::Version::parse(s).unwrap();
+    assert_eq!(semver::Version::lowest(), v("0.0.0-0"));
+    assert!(semver::Version::lowest() < v("0.0.0-alpha"));
+}
+
+// Shared versions delegate to the version they wrap.
+// This makes it possible to intern versions that are expensive to clone.
+impl<V: Version> Version for Rc<V> {
+    fn lowest() -> Self {
+        Rc::new(V::lowest())
+    }
+    fn successor(&self) -> Option<Self> {
+        V::successor(self).map(Rc::new)
+    }
+}
+
+impl<V: Version> Version for Arc<V> {
+    fn lowest() -> Self {
+        Arc::new(V::lowest())
+    }
+    fn successor(&self) -> Option<Self> {
+        V::successor(self).map(Arc::new)
+    }
+}
+
+#[test]
+fn shared_versions_delegate_to_inner_version() {
+    assert_eq!(Arc::<NumberVersion>::lowest(), Arc::new(NumberVersion(0)));
+    assert_eq!(
+        Rc::<SemanticVersion>::lowest(),
+        Rc::new(SemanticVersion::zero())
+    );
+    assert_eq!(
+        Arc::new(NumberVersion(1)).successor(),
+        Some(Arc::new(NumberVersion(2)))
+    );
+}
+
+#[test]
+fn successors() {
+    let max = u32::MAX;
+    assert_eq!(NumberVersion(1).successor(), Some(NumberVersion(2)));
+    assert_eq!(NumberVersion(max).successor(), None);
+    let v = SemanticVersion::new;
+    assert_eq!(v(1, 2, 3).successor(), Some(v(1, 2, 4)));
+    assert_eq!(v(1, 2, max).successor(), Some(v(1, 3, 0)));
+    assert_eq!(v(1, max, max).successor(), Some(v(2, 0, 0)));
+    assert_eq!(v(max, max, max).successor(), None);
+    let cal = CalVerVersion::new(2024, 1, 31, 0);
+    assert_eq!(cal.successor(), Some(CalVerVersion::new(2024, 1, 31, 1)));
+}
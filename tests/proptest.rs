@@ -166,6 +166,7 @@ pub fn registry_strategy<N: Package + Ord>(
     )
         .prop_map(
             move |(crate_vers_by_name, raw_dependencies, reverse_alphabetical, complicated_len)| {
+                #[allow(clippy::type_complexity)]
                 let mut list_of_pkgid: Vec<(
                     (N, NumberVersion),
                     Option<Vec<(N, Range<NumberVersion>)>>,
@@ -435,7 +436,7 @@ proptest! {
                     let mut smaller_dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
                     for &(n, v) in &all_versions {
                         if used.get(&n) == Some(&v) // it was used
-                           || to_remove.get(&(n, v)).is_none() // or it is not one to be removed
+                           || !to_remove.contains(&(n, v)) // or it is not one to be removed
                         {
                             let deps = match dependency_provider.get_dependencies(&n, &v).unwrap() {
                                 Dependencies::Unknown => panic!(),
@@ -457,7 +458,7 @@ proptest! {
                     // even if any version of a crate is unpublished.
                     let mut smaller_dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
                     for &(n, v) in &all_versions {
-                        if to_remove.get(&(n, v)).is_none() // it is not one to be removed
+                        if !to_remove.contains(&(n, v)) // it is not one to be removed
                         {
                             let deps = match dependency_provider.get_dependencies(&n, &v).unwrap() {
                                 Dependencies::Unknown => panic!(),
@@ -493,10 +494,10 @@ fn large_case() {
             let mut sat = SatResolve::new(&dependency_provider);
             for p in dependency_provider.packages() {
                 for n in dependency_provider.versions(p).unwrap() {
-                    if let Ok(s) = resolve(&dependency_provider, p.clone(), n.clone()) {
+                    if let Ok(s) = resolve(&dependency_provider, *p, *n) {
                         assert!(sat.sat_is_valid_solution(&s));
                     } else {
-                        assert!(!sat.sat_resolve(p, &n));
+                        assert!(!sat.sat_resolve(p, n));
                     }
                 }
             }
@@ -508,10 +509,10 @@ fn large_case() {
             let mut sat = SatResolve::new(&dependency_provider);
             for p in dependency_provider.packages() {
                 for n in dependency_provider.versions(p).unwrap() {
-                    if let Ok(s) = resolve(&dependency_provider, p.clone(), n.clone()) {
+                    if let Ok(s) = resolve(&dependency_provider, *p, *n) {
                         assert!(sat.sat_is_valid_solution(&s));
                     } else {
-                        assert!(!sat.sat_resolve(p, &n));
+                        assert!(!sat.sat_resolve(p, n));
                     }
                 }
             }
@@ -2,8 +2,16 @@
 
 use pubgrub::error::PubGrubError;
 use pubgrub::range::Range;
-use pubgrub::solver::{resolve, OfflineDependencyProvider};
-use pubgrub::version::NumberVersion;
+use pubgrub::solver::{
+    resolve, resolve_configured, resolve_with_limit, resolve_with_pins, resolve_with_stats,
+    resolve_workspace, Dependencies, DependencyProvider, OfflineDependencyProvider, SolverConfig,
+    VersionStrategy,
+};
+use pubgrub::type_aliases::Map;
+use pubgrub::version::{NumberVersion, SemanticVersion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[test]
 fn same_result_on_repeated_runs() {
@@ -52,3 +60,587 @@ fn cannot_depend_on_self() {
         Err(PubGrubError::SelfDependency { .. })
     ));
 }
+
+#[test]
+fn derivation_tree_conversions() {
+    use pubgrub::report::{Derived, External};
+    use std::convert::TryFrom;
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    let tree = match resolve(&dependency_provider, "a", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    assert!(tree.as_derived().is_some());
+    assert!(tree.as_external().is_none());
+    let tree = External::try_from(tree).unwrap_err();
+    let derived = Derived::try_from(tree).unwrap();
+    assert!(derived.cause1.as_external().is_some());
+}
+
+#[test]
+fn derivation_tree_kinds() {
+    use pubgrub::report::IncompatibilityKind;
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    let tree = match resolve(&dependency_provider, "a", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    assert_eq!(tree.kind(), IncompatibilityKind::Derived);
+    let derived = tree.as_derived().unwrap();
+    let mut kinds = vec![derived.cause1.kind(), derived.cause2.kind()];
+    kinds.sort_by_key(|kind| format!("{:?}", kind));
+    assert_eq!(
+        kinds,
+        vec![
+            IncompatibilityKind::NoVersions(&"b"),
+            IncompatibilityKind::RequiredByRoot(&"b"),
+        ]
+    );
+}
+
+#[test]
+fn stops_after_max_decisions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, [("a", Range::any())]);
+    assert!(matches!(
+        resolve_with_limit(&dependency_provider, "a", 0, 1),
+        Err(PubGrubError::LimitExceeded { decisions_made: 1 })
+    ));
+    assert!(resolve_with_limit(&dependency_provider, "a", 0, 2).is_ok());
+}
+
+#[test]
+fn stops_after_timeout() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let config = SolverConfig::default().with_timeout(Duration::ZERO);
+    assert!(matches!(
+        resolve_configured(&dependency_provider, "a", 0, config),
+        Err(PubGrubError::Timeout { .. })
+    ));
+    let config = SolverConfig::default()
+        .with_timeout(Duration::from_secs(60))
+        .with_max_decisions(2);
+    assert!(resolve_configured(&dependency_provider, "a", 0, config).is_ok());
+}
+
+#[test]
+fn stops_after_deadline_or_cancellation() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let config = SolverConfig::default().with_deadline(Instant::now());
+    assert!(matches!(
+        resolve_configured(&dependency_provider, "a", 0, config),
+        Err(PubGrubError::Timeout { .. })
+    ));
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let config = SolverConfig::default()
+        .with_deadline(Instant::now() + Duration::from_secs(60))
+        .with_cancel(cancel.clone());
+    assert!(resolve_configured(&dependency_provider, "a", 0, config.clone()).is_ok());
+    cancel.store(true, Ordering::Relaxed);
+    assert!(matches!(
+        resolve_configured(&dependency_provider, "a", 0, config),
+        Err(PubGrubError::Cancelled)
+    ));
+}
+
+#[test]
+fn dependency_cycles() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 0, [("a", Range::any())]);
+    dependency_provider.add_dependencies("a", 1, [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 1, [("a", Range::exact(1))]);
+
+    // Cycles are allowed by default.
+    assert_eq!(resolve(&dependency_provider, "root", 0).unwrap().len(), 3);
+
+    let config = SolverConfig::default().with_forbid_cycles(true);
+    match resolve_configured(&dependency_provider, "root", 0, config) {
+        Err(err @ PubGrubError::Cycle { .. }) => {
+            assert!(
+                err.to_string() == "Dependency cycle: a 1 -> b 1 -> a 1"
+                    || err.to_string() == "Dependency cycle: b 1 -> a 1 -> b 1"
+            );
+        }
+        _ => panic!("a and b depend on each other"),
+    }
+}
+
+#[test]
+fn resolution_statistics() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 0, [("foo", Range::any())]);
+    dependency_provider.add_dependencies("foo", 1, [("bar", Range::exact(1))]);
+    dependency_provider.add_dependencies("foo", 2, [("bar", Range::exact(2))]);
+    dependency_provider.add_dependencies("bar", 1, []);
+
+    let (solution, stats) = resolve_with_stats(&dependency_provider, "root", 0).unwrap();
+    assert_eq!(solution, resolve(&dependency_provider, "root", 0).unwrap());
+    assert_eq!(solution.get("foo"), Some(&NumberVersion(1)));
+    // foo 2 is picked first, bar has no version 2,
+    // so the solver must backtrack and pick foo 1 instead.
+    assert_eq!(stats.backtracks, 1);
+    assert_eq!(stats.decisions, 4);
+    assert_eq!(stats.max_decision_level, 3);
+    assert!(stats.derivations >= solution.len());
+    assert!(stats.incompatibilities_added >= 4);
+}
+
+#[test]
+fn workspace_members_share_dependencies() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("app", 1, [("log", Range::between(1, 3))]);
+    dependency_provider.add_dependencies("cli", 1, [("log", Range::higher_than(2))]);
+    dependency_provider.add_dependencies("log", 1, []);
+    dependency_provider.add_dependencies("log", 2, []);
+    dependency_provider.add_dependencies("log", 3, []);
+
+    let solution =
+        resolve_workspace(&dependency_provider, [("app", 1.into()), ("cli", 1.into())]).unwrap();
+    assert_eq!(solution.len(), 3);
+    assert_eq!(solution.get("app"), Some(&NumberVersion(1)));
+    assert_eq!(solution.get("cli"), Some(&NumberVersion(1)));
+    assert_eq!(solution.get("log"), Some(&NumberVersion(2)));
+
+    // Members requiring conflicting versions of a shared transitive dependency.
+    dependency_provider.add_dependencies("app", 2, [("log", Range::exact(1))]);
+    dependency_provider.add_dependencies("cli", 2, [("log", Range::exact(3))]);
+    match resolve_workspace(&dependency_provider, [("app", 2.into()), ("cli", 2.into())]) {
+        Err(PubGrubError::NoSolution(tree)) => {
+            use pubgrub::report::{DefaultStringReporter, Reporter};
+            let report = DefaultStringReporter::report(&tree);
+            assert!(
+                report.ends_with(
+                    "And because root requires app 2 and root requires cli 2, \
+                     root is forbidden."
+                ),
+                "{}",
+                report
+            );
+        }
+        _ => panic!("the members should conflict"),
+    }
+}
+
+#[test]
+fn keep_pinned_versions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 1, [("foo", Range::any())]);
+    dependency_provider.add_dependencies("root", 2, [("foo", Range::any()), ("bar", Range::any())]);
+    dependency_provider.add_dependencies("foo", 1, []);
+    dependency_provider.add_dependencies("foo", 2, []);
+    dependency_provider.add_dependencies("bar", 1, [("foo", Range::higher_than(2))]);
+    let mut pins = Map::default();
+    pins.insert("foo", NumberVersion(1));
+
+    // The pinned version is kept even though a newer one exists.
+    let pinned = resolve_with_pins(&dependency_provider, "root", 1, &pins).unwrap();
+    assert_eq!(pinned.solution().get("foo"), Some(&NumberVersion(1)));
+    assert_eq!(pinned.honored().count(), 1);
+    assert_eq!(pinned.relaxed().count(), 0);
+
+    // A new dependency requires a newer version, so the pin is relaxed.
+    let pinned = resolve_with_pins(&dependency_provider, "root", 2, &pins).unwrap();
+    assert_eq!(pinned.solution().get("foo"), Some(&NumberVersion(2)));
+    assert_eq!(pinned.honored().count(), 0);
+    assert_eq!(
+        pinned.relaxed().collect::<Vec<_>>(),
+        vec![(&"foo", &NumberVersion(1))]
+    );
+}
+
+#[test]
+fn root_dependencies_are_reported_as_such() {
+    use pubgrub::report::{DefaultStringReporter, Reporter};
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::higher_than(1))]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let tree = match resolve(&dependency_provider, "a", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    assert!(DefaultStringReporter::report(&tree).contains("root requires b 1 <= v"));
+}
+
+#[test]
+fn dependency_provenance_is_reported() {
+    use pubgrub::report::{DefaultStringReporter, Reporter};
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 0, [("a", Range::any())]);
+    let from_manifest = Range::higher_than(1).with_provenance("a/Cargo.toml:14");
+    dependency_provider.add_dependencies("a", 0, [("b", from_manifest)]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let tree = match resolve(&dependency_provider, "root", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    let report = DefaultStringReporter::report(&tree);
+    assert!(report.contains("a 0 depends on b 1 <= v (from a/Cargo.toml:14)"));
+}
+
+#[test]
+fn version_strategies() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any())]);
+    for v in [1, 3, 5, 7] {
+        dependency_provider.add_dependencies("b", v, []);
+    }
+    let picked_b = |strategy| {
+        let dependency_provider = dependency_provider.clone().with_strategy(strategy);
+        resolve(&dependency_provider, "a", 0).unwrap()[&"b"]
+    };
+    assert_eq!(picked_b(VersionStrategy::Maximum), NumberVersion(7));
+    assert_eq!(picked_b(VersionStrategy::Minimum), NumberVersion(1));
+    let pins = |v| std::iter::once(("b", NumberVersion(v))).collect();
+    assert_eq!(
+        picked_b(VersionStrategy::Closest(pins(3))),
+        NumberVersion(3)
+    );
+    assert_eq!(
+        picked_b(VersionStrategy::Closest(pins(4))),
+        NumberVersion(5)
+    );
+    assert_eq!(
+        picked_b(VersionStrategy::Closest(pins(8))),
+        NumberVersion(7)
+    );
+}
+
+#[test]
+fn add_dependencies_to_a_range_of_versions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    for v in 0..4 {
+        dependency_provider.add_dependencies("a", v, []);
+    }
+    dependency_provider.add_dependencies_range("a", Range::between(1, 3), [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, []);
+    assert!(!resolve(&dependency_provider, "a", 0)
+        .unwrap()
+        .contains_key(&"b"));
+    assert!(resolve(&dependency_provider, "a", 1)
+        .unwrap()
+        .contains_key(&"b"));
+    assert!(resolve(&dependency_provider, "a", 2)
+        .unwrap()
+        .contains_key(&"b"));
+    assert!(!resolve(&dependency_provider, "a", 3)
+        .unwrap()
+        .contains_key(&"b"));
+}
+
+#[test]
+fn add_dependencies_to_a_range_of_unregistered_versions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies_range("a", Range::between(1, 3), [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let versions: Vec<_> = dependency_provider.versions(&"a").unwrap().collect();
+    assert_eq!(versions, [&NumberVersion(1), &NumberVersion(2)]);
+    assert!(resolve(&dependency_provider, "a", 2)
+        .unwrap()
+        .contains_key(&"b"));
+}
+
+#[test]
+fn add_dependencies_to_a_range_of_semantic_versions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, SemanticVersion>::new();
+    let range = Range::between((1, 0, 0), (2, 0, 0));
+    dependency_provider.add_dependencies_range("a", range, [("b", Range::any())]);
+    dependency_provider.add_dependencies("a", (1, 5, 0), []);
+    dependency_provider.add_dependencies("b", (0, 1, 0), []);
+    let deps = |v: (u32, u32, u32)| match dependency_provider
+        .get_dependencies(&"a", &v.into())
+        .unwrap()
+    {
+        Dependencies::Known(deps) => Some(deps),
+        Dependencies::Unknown => None,
+    };
+    assert!(deps((1, 2, 0)).unwrap().contains_key(&"b"));
+    assert!(deps((1, 5, 0)).unwrap().is_empty());
+    assert!(deps((2, 0, 0)).is_none());
+}
+
+#[test]
+fn known_versions() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("a", 2, [("b", Range::any())]);
+    dependency_provider.add_dependencies("a", 0, []);
+    dependency_provider.add_dependencies("b", 1, []);
+
+    let versions: Vec<_> = dependency_provider.versions_for(&"a").collect();
+    assert_eq!(versions, vec![&NumberVersion(0), &NumberVersion(2)]);
+    assert_eq!(dependency_provider.versions_for(&"c").count(), 0);
+    assert!(dependency_provider.contains(&"a", &NumberVersion(2)));
+    assert!(!dependency_provider.contains(&"a", &NumberVersion(1)));
+    assert!(!dependency_provider.contains(&"c", &NumberVersion(0)));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_registry_round_trip() {
+    use pubgrub::version::SemanticVersion;
+    let json = std::fs::read_to_string("examples/registry.json").unwrap();
+    let dependency_provider =
+        OfflineDependencyProvider::<String, SemanticVersion>::from_json(json.as_bytes()).unwrap();
+    assert_eq!(dependency_provider.graph_size(), (7, 6));
+
+    let mut written = Vec::new();
+    dependency_provider.to_json(&mut written).unwrap();
+    let read_again =
+        OfflineDependencyProvider::<String, SemanticVersion>::from_json(written.as_slice())
+            .unwrap();
+    let mut written_again = Vec::new();
+    read_again.to_json(&mut written_again).unwrap();
+    assert_eq!(written, written_again);
+
+    let root = "root".to_string();
+    let solution = resolve(&read_again, root.clone(), (1, 0, 0)).unwrap();
+    assert_eq!(
+        solution,
+        resolve(&dependency_provider, root, (1, 0, 0)).unwrap()
+    );
+    assert_eq!(solution.get("menu"), Some(&SemanticVersion::new(1, 1, 0)));
+    assert_eq!(
+        solution.get("dropdown"),
+        Some(&SemanticVersion::new(1, 0, 0))
+    );
+
+    let not_a_registry = r#"{ "root": {} }"#;
+    assert!(
+        OfflineDependencyProvider::<String, SemanticVersion>::from_json(not_a_registry.as_bytes())
+            .is_err()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn derivation_tree_serde_round_trip() {
+    use pubgrub::report::{DefaultStringReporter, DerivationTree, Reporter};
+    let mut dependency_provider = OfflineDependencyProvider::<String, NumberVersion>::new();
+    let p = |name: &str| name.to_string();
+    dependency_provider.add_dependencies(
+        p("root"),
+        0,
+        [(p("a"), Range::any()), (p("b"), Range::any())],
+    );
+    dependency_provider.add_dependencies(p("a"), 0, [(p("c"), Range::exact(1))]);
+    dependency_provider.add_dependencies(p("b"), 0, [(p("c"), Range::exact(2))]);
+    dependency_provider.add_dependencies(p("c"), 1, []);
+    let tree = match resolve(&dependency_provider, "root".to_string(), 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("a and b depend on different versions of c"),
+    };
+
+    let json = serde_json::to_string(&tree).unwrap();
+    let read: DerivationTree<String, NumberVersion> = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    assert_eq!(
+        DefaultStringReporter::report(&read),
+        DefaultStringReporter::report(&tree)
+    );
+}
+
+#[test]
+fn graph_size() {
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    assert_eq!(dependency_provider.graph_size(), (0, 0));
+    assert_eq!(dependency_provider.average_degree(), 0.0);
+    dependency_provider.add_dependencies("a", 0, [("b", Range::any()), ("c", Range::any())]);
+    dependency_provider.add_dependencies("a", 1, [("b", Range::any())]);
+    dependency_provider.add_dependencies("b", 0, []);
+    assert_eq!(dependency_provider.graph_size(), (3, 3));
+    assert_eq!(dependency_provider.average_degree(), 1.0);
+}
+
+#[test]
+fn report_with_max_depth() {
+    use pubgrub::report::{DefaultStringReporter, Reporter};
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 0, [("a", Range::any())]);
+    dependency_provider.add_dependencies("a", 0, [("b", Range::higher_than(1))]);
+    dependency_provider.add_dependencies("b", 0, []);
+    let tree = match resolve(&dependency_provider, "root", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    let depth = tree.depth();
+    assert!(depth > 1);
+    assert_eq!(
+        DefaultStringReporter::report_with_max_depth(&tree, depth),
+        DefaultStringReporter::report(&tree)
+    );
+    let truncated = DefaultStringReporter::report_with_max_depth(&tree, depth - 1);
+    assert!(truncated.contains("explanation omitted"));
+    assert_eq!(truncated.lines().count(), 1);
+}
+
+#[test]
+fn report_with_formatter() {
+    use pubgrub::range::RangeFormatter;
+    use pubgrub::report::{DefaultStringReporter, Reporter};
+    use std::fmt;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    struct Comparators;
+    impl RangeFormatter<NumberVersion> for Comparators {
+        fn fmt_range(
+            &self,
+            range: &Range<NumberVersion>,
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let (start, end) = match range.iter_segments().next() {
+                Some(segment) => segment,
+                None => return write!(f, "none"),
+            };
+            let start = match start {
+                Included(v) => Some(format!(">={}", v)),
+                Excluded(v) => Some(format!(">{}", v)),
+                Unbounded => None,
+            };
+            let end = match end {
+                Included(v) => Some(format!("<={}", v)),
+                Excluded(v) => Some(format!("<{}", v)),
+                Unbounded => None,
+            };
+            let comparators: Vec<_> = start.into_iter().chain(end).collect();
+            write!(f, "{}", comparators.join(" "))
+        }
+    }
+
+    let mut dependency_provider = OfflineDependencyProvider::<_, NumberVersion>::new();
+    dependency_provider.add_dependencies("root", 0, [("a", Range::between(2, 4))]);
+    dependency_provider.add_dependencies("a", 1, []);
+    let tree = match resolve(&dependency_provider, "root", 0) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    let report = DefaultStringReporter::report_with_formatter(&tree, &Comparators);
+    assert!(report.contains("root requires a >=2 <4"), "{}", report);
+    assert!(!report.contains("<= v"), "{}", report);
+    assert_eq!(
+        DefaultStringReporter::report_with_formatter(&tree, &pubgrub::range::DefaultRangeFormatter),
+        DefaultStringReporter::report(&tree)
+    );
+}
+
+#[test]
+fn structured_report() {
+    use pubgrub::report::{DefaultStringReporter, ReportLineKind, Reporter};
+
+    // https://github.com/dart-lang/pub/blob/master/doc/solver.md#branching-error-reporting
+    let mut dependency_provider = OfflineDependencyProvider::<&str, NumberVersion>::new();
+    #[rustfmt::skip]
+    dependency_provider.add_dependencies(
+        "root", 1, [("foo", Range::between(10, 20))],
+    );
+    #[rustfmt::skip]
+    dependency_provider.add_dependencies(
+        "foo", 10, [("a", Range::between(10, 20)), ("b", Range::between(10, 20))],
+    );
+    #[rustfmt::skip]
+    dependency_provider.add_dependencies(
+        "foo", 11, [("x", Range::between(10, 20)), ("y", Range::between(10, 20))],
+    );
+    dependency_provider.add_dependencies("a", 10, [("b", Range::between(20, 30))]);
+    dependency_provider.add_dependencies("b", 10, []);
+    dependency_provider.add_dependencies("b", 20, []);
+    dependency_provider.add_dependencies("x", 10, [("y", Range::between(20, 30))]);
+    dependency_provider.add_dependencies("y", 10, []);
+    dependency_provider.add_dependencies("y", 20, []);
+    let mut tree = match resolve(&dependency_provider, "root", 1) {
+        Err(PubGrubError::NoSolution(tree)) => tree,
+        _ => panic!("expected no solution"),
+    };
+    tree.collapse_no_versions();
+
+    // Output before reports were structured, without indentation.
+    let flat = "Because a 10 <= v < 20 depends on b 20 <= v < 30 and foo 10 depends on b 10 <= v < 20, foo 10, a 10 <= v < 20 are incompatible.
+And because foo 10 depends on a 10 <= v < 20, foo 10 is forbidden. (1)
+
+Because x 10 <= v < 20 depends on y 20 <= v < 30 and foo 11 depends on x 10 <= v < 20, foo 11 depends on y 20 <= v < 30.
+And because foo 11 depends on y 10 <= v < 20, foo 11 is forbidden.
+And because foo 10 is forbidden (1), foo 10 <= v < 20 is forbidden.
+And because root requires foo 10 <= v < 20, root 1 is forbidden.";
+    let indented = "  Because a 10 <= v < 20 depends on b 20 <= v < 30 and foo 10 depends on b 10 <= v < 20, foo 10, a 10 <= v < 20 are incompatible.
+  And because foo 10 depends on a 10 <= v < 20, foo 10 is forbidden. (1)
+
+Because x 10 <= v < 20 depends on y 20 <= v < 30 and foo 11 depends on x 10 <= v < 20, foo 11 depends on y 20 <= v < 30.
+And because foo 11 depends on y 10 <= v < 20, foo 11 is forbidden.
+And because foo 10 is forbidden (1), foo 10 <= v < 20 is forbidden.
+And because root requires foo 10 <= v < 20, root 1 is forbidden.";
+
+    let report = tree.to_report();
+    assert_eq!(report.to_string(), indented);
+    assert_eq!(DefaultStringReporter::report(&tree), indented);
+    let trimmed: Vec<_> = indented.lines().map(str::trim_start).collect();
+    assert_eq!(trimmed.join("\n"), flat);
+
+    let lines = report.lines();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[1].line_ref, Some(1));
+    assert!(lines[2].new_paragraph);
+    assert_eq!(lines[2].kind, ReportLineKind::Dependency);
+    assert_eq!(lines[4].kind, ReportLineKind::Conflict);
+    let indents: Vec<_> = lines.iter().map(|line| line.indent).collect();
+    assert_eq!(indents, vec![1, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn format_styles() {
+    use pubgrub::report::{DerivationTree, Derived, External, ReportStyle};
+    use pubgrub::term::Term;
+
+    let a_depends_on_b = DerivationTree::External(External::FromDependencyOf(
+        "a",
+        Range::exact(1),
+        "b",
+        Range::exact(2),
+    ));
+    let no_b = DerivationTree::External(External::NoVersions("b", Range::exact(2)));
+    assert_eq!(a_depends_on_b.format_long(), "a 1 depends on b 2");
+    assert_eq!(a_depends_on_b.format_short(), "a ∧ b conflicts");
+    assert_eq!(no_b.format_with_style(ReportStyle::Short), "b conflicts");
+
+    let mut terms = Map::default();
+    terms.insert("a", Term::Positive(Range::exact(1)));
+    let derived: DerivationTree<&str, NumberVersion> = DerivationTree::Derived(Derived {
+        terms,
+        shared_id: None,
+        cause1: Box::new(a_depends_on_b),
+        cause2: Box::new(no_b),
+    });
+    assert_eq!(
+        derived.format_with_style(ReportStyle::Long),
+        "a 1 is forbidden"
+    );
+    assert_eq!(derived.format_short(), "a conflicts");
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn resolve_semver_requirements() {
+    let v = |s: &str| semver::Version::parse(s).unwrap();
+    let req = |s: &str| Range::from_requirement(&semver::VersionReq::parse(s).unwrap()).unwrap();
+    let mut dependency_provider = OfflineDependencyProvider::<&str, semver::Version>::new();
+    dependency_provider.add_dependencies("root", v("1.0.0"), [("menu", req("^1.0"))]);
+    dependency_provider.add_dependencies("menu", v("1.0.0"), [("icons", req("~0.5"))]);
+    dependency_provider.add_dependencies("menu", v("1.1.0"), [("icons", req(">=0.6, <1.0"))]);
+    dependency_provider.add_dependencies("menu", v("2.0.0"), []);
+    dependency_provider.add_dependencies("icons", v("0.5.3"), []);
+    dependency_provider.add_dependencies("icons", v("0.6.0-beta"), []);
+
+    let solution = resolve(&dependency_provider, "root", v("1.0.0")).unwrap();
+    assert_eq!(solution.get("menu"), Some(&v("1.0.0")));
+    assert_eq!(solution.get("icons"), Some(&v("0.5.3")));
+}